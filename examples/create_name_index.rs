@@ -13,7 +13,7 @@ impl HasIndex for TestDocument {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     TestDocument::ensure_index(&client).await?;
 
@@ -1,6 +1,6 @@
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     client
         .delete_all_documents("names")
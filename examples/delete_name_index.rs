@@ -9,7 +9,7 @@ impl HasIndex for TestDocument {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     println!(
         "Deleting index: {:?}",
@@ -7,7 +7,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(|s| s.trim().parse::<u64>())
         .collect::<Result<Vec<_>, _>>()?;
 
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     client
         .delete_documents("names", &ids)
@@ -1,4 +1,4 @@
-use minimeili::{prelude::*, Search};
+use minimeili::{prelude::*, Filter, Search};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct User {
@@ -40,7 +40,7 @@ impl HasIndex for User {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     let task_ref = User::delete_index(&client).await?;
     task_ref.wait_until_stopped(&client).await?;
@@ -61,7 +61,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!(
         "Filter on 23:\n:{:#?}",
-        User::search(&client, Search::new("").filter(Some("sites.id = 23"))).await?
+        User::search(
+            &client,
+            Search::new("").filter(Some(Filter::eq("sites.id", 23)))
+        )
+        .await?
     );
     println!();
     println!(
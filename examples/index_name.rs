@@ -2,7 +2,7 @@ use minimeili::prelude::*;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     let name = std::env::args()
         .nth(1)
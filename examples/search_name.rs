@@ -14,7 +14,7 @@ impl minimeili::HasIndex for TestDocument {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     let qry = std::env::args()
         .nth(1)
@@ -43,7 +43,7 @@ impl HasIndex for Outer {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = minimeili::Client::from_env();
+    let client = minimeili::Client::from_env()?;
 
     let task_ref = Outer::delete_index(&client).await?;
     task_ref.wait_until_stopped(&client).await?;
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::{DateTime, TaskKindRef, TaskStatus};
+
+/// Group of tasks processed together by meilisearch.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Batch {
+    pub uid: u64,
+    /// None once the batch has finished
+    pub progress: Option<ProcessingProgress>,
+    /// Details of the batched tasks, aggregated
+    #[serde(default)]
+    pub details: serde_json::Map<String, serde_json::Value>,
+    pub stats: BatchStats,
+    pub duration: Option<String>,
+    pub started_at: DateTime,
+    pub finished_at: Option<DateTime>,
+    /// Why meilisearch stopped adding tasks to the batch
+    pub batch_strategy: Option<String>,
+}
+
+crate::datetime::timestamp_accessors!(Batch {
+    started_at => started_at_chrono, started_at_time;
+} optional {
+    finished_at => finished_at_chrono, finished_at_time;
+});
+
+impl Batch {
+    /// `duration` parsed, None if unset or invalid
+    pub fn duration_std(&self) -> Option<std::time::Duration> {
+        crate::datetime::parse_iso8601_duration(self.duration.as_deref()?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessingProgress {
+    /// Nested steps, from the outermost to the current one
+    pub steps: Vec<ProgressStep>,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressStep {
+    pub current_step: String,
+    pub finished: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStats {
+    pub total_nb_tasks: u64,
+    pub status: HashMap<TaskStatus, u64>,
+    pub types: HashMap<TaskKindRef, u64>,
+    pub index_uids: HashMap<String, u64>,
+    /// Time spent in each processing step, such as
+    /// `"processing tasks > indexing": "1.2s"`
+    #[serde(default)]
+    pub progress_trace: HashMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchesPage {
+    /// Batches in descending order of uid
+    pub results: Vec<Batch>,
+
+    /// Total number of batches matching the query
+    pub total: u64,
+
+    pub limit: u32,
+
+    pub from: Option<u64>,
+
+    /// Value of `from` for the next page, None on the last page
+    pub next: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn deserialize_processing_batch() {
+        let s = r#"
+{"uid":12,"progress":{"steps":[{"currentStep":"processing tasks","finished":0,"total":2},
+ {"currentStep":"indexing","finished":1,"total":3}],"percentage":16.666},
+ "details":{"receivedDocuments":2000,"indexedDocuments":null},
+ "stats":{"totalNbTasks":2,"status":{"processing":2},"types":{"documentAdditionOrUpdate":2},
+ "indexUids":{"users":2},"progressTrace":{}},
+ "duration":null,"startedAt":"2024-01-01T00:00:00Z","finishedAt":null,"batchStrategy":null}"#;
+
+        let batch = serde_json::from_str::<Batch>(s).expect("deser");
+
+        assert_eq!(batch.progress.map(|p| p.steps.len()), Some(2));
+        assert_eq!(batch.stats.status.get(&TaskStatus::Processing), Some(&2));
+        assert_eq!(
+            batch
+                .stats
+                .types
+                .get(&TaskKindRef::DocumentAdditionOrUpdate),
+            Some(&2)
+        );
+        assert_eq!(batch.details["receivedDocuments"], 2000);
+    }
+}
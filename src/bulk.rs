@@ -0,0 +1,332 @@
+use futures::{
+    stream::{self, Fuse},
+    Stream, StreamExt, TryStreamExt,
+};
+use reqwest::Method;
+use std::{pin::Pin, sync::Arc};
+use tracing::info;
+
+#[cfg(feature = "tokio")]
+use crate::WaitOptions;
+use crate::{Client, Error, HasIndex, Result, TaskRef};
+
+const DEFAULT_MAX_DOCUMENTS: usize = 10_000;
+const DEFAULT_MAX_BYTES: usize = 50 * 1024 * 1024;
+const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Reported after each uploaded batch
+#[derive(Debug)]
+pub struct BatchProgress<'a> {
+    /// Index of the batch, starting at 0
+    pub batch: usize,
+    pub documents: usize,
+    pub bytes: usize,
+    pub task: &'a TaskRef,
+}
+
+type ProgressFn = Arc<dyn Fn(&BatchProgress<'_>) + Send + Sync>;
+
+/// Uploads large amounts of documents, split into batches by document
+/// count and serialized size.
+///
+/// A single document larger than `max_bytes` is sent in a batch of its own.
+pub struct BulkIndexer {
+    client: Client,
+    index_uid: Option<String>,
+    method: Method,
+    max_documents: usize,
+    max_bytes: usize,
+    concurrency: usize,
+    #[cfg(feature = "tokio")]
+    wait: Option<WaitOptions>,
+    on_progress: Option<ProgressFn>,
+}
+
+impl BulkIndexer {
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+            index_uid: None,
+            method: Method::POST,
+            max_documents: DEFAULT_MAX_DOCUMENTS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            concurrency: DEFAULT_CONCURRENCY,
+            #[cfg(feature = "tokio")]
+            wait: None,
+            on_progress: None,
+        }
+    }
+
+    /// Index to upload to
+    /// default `T::INDEX_UID`
+    pub fn index_uid(mut self, index_uid: impl Into<String>) -> Self {
+        self.index_uid = Some(index_uid.into());
+        self
+    }
+
+    /// Replace existing documents, this is the default
+    pub fn replace(mut self) -> Self {
+        self.method = Method::POST;
+        self
+    }
+
+    /// Update existing documents, keeping fields missing in the new ones
+    pub fn update(mut self) -> Self {
+        self.method = Method::PUT;
+        self
+    }
+
+    /// default 10 000
+    pub fn max_documents(mut self, n: usize) -> Self {
+        self.max_documents = n.max(1);
+        self
+    }
+
+    /// Maximum size of a serialized batch
+    /// default 50MiB
+    pub fn max_bytes(mut self, n: usize) -> Self {
+        self.max_bytes = n;
+        self
+    }
+
+    /// Number of batches uploaded at the same time
+    /// default 2
+    pub fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = n.max(1);
+        self
+    }
+
+    /// Wait for all tasks to stop before returning, fails with
+    /// [`Error::TaskFailed`] if any of them did not succeed.
+    #[cfg(feature = "tokio")]
+    pub fn wait(mut self, wait: bool) -> Self {
+        self.wait = wait.then(|| self.client.wait_options().clone());
+        self
+    }
+
+    /// Like [`BulkIndexer::wait`], using `opts` instead of the
+    /// client's [`WaitOptions`]
+    #[cfg(feature = "tokio")]
+    pub fn wait_with(mut self, opts: WaitOptions) -> Self {
+        self.wait = Some(opts);
+        self
+    }
+
+    /// Called after each uploaded batch
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&BatchProgress<'_>) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Arc::new(f));
+        self
+    }
+
+    pub async fn index<T>(self, docs: impl IntoIterator<Item = T>) -> Result<Vec<TaskRef>>
+    where
+        T: HasIndex,
+        T: serde::Serialize,
+    {
+        self.index_stream(stream::iter(docs)).await
+    }
+
+    pub async fn index_stream<T>(self, docs: impl Stream<Item = T>) -> Result<Vec<TaskRef>>
+    where
+        T: HasIndex,
+        T: serde::Serialize,
+    {
+        let index_uid = self.index_uid.unwrap_or_else(|| String::from(T::INDEX_UID));
+
+        let batches = Batches::new(docs, self.max_documents, self.max_bytes);
+
+        let task_refs = stream::try_unfold(batches, Batches::next)
+            .enumerate()
+            .map(|(n, batch)| batch.map(|batch| (n, batch)))
+            .map_ok(|(n, batch)| {
+                let client = &self.client;
+                let index_uid = &index_uid;
+                let method = self.method.clone();
+                let on_progress = self.on_progress.clone();
+
+                async move {
+                    let (documents, bytes) = (batch.documents, batch.body.len());
+
+                    let task = client
+                        .add_documents_raw(method, index_uid, "application/json", batch.body)
+                        .await?;
+
+                    info!(
+                        "meili: uploaded batch {n} of {documents} documents ({bytes} bytes) to `{index_uid}`, task {}",
+                        task.uid
+                    );
+
+                    if let Some(f) = on_progress {
+                        f(&BatchProgress {
+                            batch: n,
+                            documents,
+                            bytes,
+                            task: &task,
+                        });
+                    }
+
+                    Ok(task)
+                }
+            })
+            .try_buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        #[cfg(feature = "tokio")]
+        if let Some(opts) = &self.wait {
+            let mut report = self.client.wait_for_tasks_with(&task_refs, opts).await?;
+
+            if let Some(task) = report.failed.pop().or_else(|| report.canceled.pop()) {
+                return Err(Error::TaskFailed(Box::new(task)));
+            }
+        }
+
+        Ok(task_refs)
+    }
+}
+
+struct Batch {
+    body: Vec<u8>,
+    documents: usize,
+}
+
+struct Batches<S> {
+    /// Fused, since the last batch polls it again after it ended
+    docs: Pin<Box<Fuse<S>>>,
+    /// Document which did not fit in the previous batch
+    carry: Option<Vec<u8>>,
+    max_documents: usize,
+    max_bytes: usize,
+}
+
+impl<S> Batches<S>
+where
+    S: Stream,
+    S::Item: serde::Serialize,
+{
+    fn new(docs: S, max_documents: usize, max_bytes: usize) -> Self {
+        Self {
+            docs: Box::pin(docs.fuse()),
+            carry: None,
+            max_documents,
+            max_bytes,
+        }
+    }
+
+    async fn next(mut self) -> Result<Option<(Batch, Self)>> {
+        let mut batch = Batch {
+            body: Vec::from(b"["),
+            documents: 0,
+        };
+
+        if let Some(doc) = self.carry.take() {
+            batch.push(&doc);
+        }
+
+        while batch.documents < self.max_documents {
+            let Some(doc) = self.docs.next().await else {
+                break;
+            };
+
+            let doc = serde_json::to_vec(&doc).map_err(Error::Serialize)?;
+
+            // `,` and `]`
+            if 0 < batch.documents && self.max_bytes < batch.body.len() + doc.len() + 2 {
+                self.carry = Some(doc);
+                break;
+            }
+
+            batch.push(&doc);
+        }
+
+        if batch.documents == 0 {
+            return Ok(None);
+        }
+
+        batch.body.push(b']');
+
+        Ok(Some((batch, self)))
+    }
+}
+
+impl Batch {
+    fn push(&mut self, doc: &[u8]) {
+        if 0 < self.documents {
+            self.body.push(b',');
+        }
+        self.body.extend_from_slice(doc);
+        self.documents += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(serde::Serialize)]
+    struct Doc {
+        id: u64,
+    }
+
+    async fn collect_batches(
+        docs: impl Stream<Item = Doc>,
+        max_documents: usize,
+        max_bytes: usize,
+    ) -> Vec<String> {
+        let batches = Batches::new(docs, max_documents, max_bytes);
+
+        stream::try_unfold(batches, Batches::next)
+            .map_ok(|batch| String::from_utf8(batch.body).expect("utf8"))
+            .try_collect()
+            .await
+            .expect("batches")
+    }
+
+    #[tokio::test]
+    async fn batches_split_by_count() {
+        let docs = stream::iter((0..5).map(|id| Doc { id }));
+
+        assert_eq!(
+            collect_batches(docs, 2, usize::MAX).await,
+            [
+                r#"[{"id":0},{"id":1}]"#,
+                r#"[{"id":2},{"id":3}]"#,
+                r#"[{"id":4}]"#
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_split_by_size() {
+        let docs = vec![Doc { id: 1 }, Doc { id: 22 }, Doc { id: 333 }];
+
+        // `[{"id":1},{"id":22}]` is 20 bytes
+        assert_eq!(
+            collect_batches(stream::iter(docs), 10, 20).await,
+            [r#"[{"id":1},{"id":22}]"#, r#"[{"id":333}]"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn no_batches_without_documents() {
+        assert!(collect_batches(stream::empty(), 10, 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stream_not_polled_after_end() {
+        // Panics if polled again after returning None
+        let docs = stream::unfold(
+            0,
+            |id| async move { (id < 3).then(|| (Doc { id }, id + 1)) },
+        );
+
+        assert_eq!(
+            collect_batches(docs, 2, usize::MAX).await,
+            [r#"[{"id":0},{"id":1}]"#, r#"[{"id":2}]"#]
+        );
+    }
+}
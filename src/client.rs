@@ -1,21 +1,46 @@
+use futures::Stream;
 use reqwest::{header, Method};
-use std::{env, sync::Arc};
-use tracing::{info, warn};
+use std::sync::Arc;
+use tracing::warn;
 
 use crate::{
-    index::Index,
+    batch::{Batch, BatchesPage},
+    bulk::BulkIndexer,
+    documents::{self, DocumentsBody, DocumentsPage, DocumentsQuery},
+    index::{self, Index, IndexStats, IndexesPage},
+    multi_search::{
+        FederatedSearch, FederatedSearchResponse, MultiSearchQueries, MultiSearchRequest,
+        MultiSearchResponse,
+    },
+    retry::{self, RetryPolicy},
     search::{Search, SearchResponse},
-    task::{AsTaskUid, Task, TaskRef},
-    Error, HasIndex, IndexSettings, Result,
+    settings::{Setting, SettingsPatch},
+    task::{AsTaskUid, IndexSwap, Task, TaskRef},
+    task_query::{self, TaskQuery, TasksPage},
+    Error, Filter, HasIndex, IndexSettings, Result,
+};
+
+#[cfg(feature = "tokio")]
+use {
+    crate::wait::{TasksReport, WaitOptions},
+    std::collections::BTreeSet,
 };
 
 #[cfg(feature = "hooks")]
-use crate::TaskManager;
+use crate::{TaskManager, WebhookHandler};
+
+mod builder;
+
+pub use builder::{BuildError, ClientBuilder};
 
 #[derive(Clone)]
 pub struct Client {
     c: reqwest::Client,
     base_url: Arc<String>,
+    headers: Arc<header::HeaderMap>,
+    retry: Arc<RetryPolicy>,
+    #[cfg(feature = "tokio")]
+    wait_options: Arc<WaitOptions>,
 
     #[cfg(feature = "hooks")]
     task_manager: TaskManager,
@@ -64,6 +89,30 @@ where
     }
 }
 
+/// Body which is already serialized
+struct Raw {
+    content_type: &'static str,
+    body: reqwest::Body,
+}
+
+impl Payload for Raw {
+    fn set_to(self, rb: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        rb.body(self.body).header("content-type", self.content_type)
+    }
+}
+
+/// Parameters sent in the query string
+struct Query<'a, T>(&'a T);
+
+impl<T> Payload for Query<'_, T>
+where
+    T: serde::Serialize,
+{
+    fn set_to(self, rb: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        rb.query(self.0)
+    }
+}
+
 struct Empty;
 
 impl Payload for Empty {
@@ -86,7 +135,9 @@ impl Client {
             self.base_url.as_str().trim_end_matches('/'),
             path.trim_start_matches('/')
         );
-        self.c.request(m, url)
+        self.c
+            .request(m, url)
+            .headers(self.headers.as_ref().clone())
     }
 
     #[cfg(all(feature = "tokio", feature = "hooks"))]
@@ -100,15 +151,80 @@ impl Client {
         Ok(())
     }
 
+    /// Handler for meilisearch task webhooks, feeding this client's [`TaskManager`]
+    #[cfg(feature = "hooks")]
+    pub fn webhook_handler(&self) -> WebhookHandler {
+        WebhookHandler::new(self.task_manager.clone())
+    }
+
     async fn req<R>(&self, method: Method, path: &str, payload: impl Payload) -> Result<R::Output>
     where
         R: FromResponse,
     {
-        let http_res = match payload
-            .set_to(self.build_request(method, path))
-            .send()
-            .await
-        {
+        let idempotent = !matches!(method, Method::POST | Method::PATCH);
+        self.send::<R>(method, path, payload, idempotent).await
+    }
+
+    /// For POST requests which only read data, such as searches,
+    /// and therefore are safe to retry.
+    async fn req_idempotent<R>(
+        &self,
+        method: Method,
+        path: &str,
+        payload: impl Payload,
+    ) -> Result<R::Output>
+    where
+        R: FromResponse,
+    {
+        self.send::<R>(method, path, payload, true).await
+    }
+
+    async fn send<R>(
+        &self,
+        method: Method,
+        path: &str,
+        payload: impl Payload,
+        idempotent: bool,
+    ) -> Result<R::Output>
+    where
+        R: FromResponse,
+    {
+        let mut request = payload.set_to(self.build_request(method, path)).build()?;
+
+        let mut attempt = 1;
+
+        loop {
+            // Streaming bodies can not be cloned, and thus not retried.
+            let next = if attempt < self.retry.attempts() {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            match (self.execute::<R>(request).await, next) {
+                (Err(err), Some(next)) if self.retry.should_retry(&err, idempotent) => {
+                    let delay = self.retry.backoff(attempt);
+                    warn!(
+                        "meili: attempt {attempt} of {} failed, retrying in {delay:?}: {err}",
+                        self.retry.attempts()
+                    );
+
+                    retry::sleep(delay).await;
+
+                    request = next;
+                    attempt += 1;
+                }
+
+                (res, _) => return res,
+            }
+        }
+    }
+
+    async fn execute<R>(&self, request: reqwest::Request) -> Result<R::Output>
+    where
+        R: FromResponse,
+    {
+        let http_res = match self.c.execute(request).await {
             Ok(res) => res,
 
             Err(err) => {
@@ -121,12 +237,23 @@ impl Client {
             R::from_response(http_res).await
         } else {
             let code = http_res.status().as_u16();
-            let body = http_res.text().await?;
+            let body = http_res.bytes().await?;
 
-            Err(Error::UnexpectedNok {
-                code,
-                body: if body.is_empty() { None } else { Some(body) },
-            })
+            match serde_json::from_slice(&body) {
+                Ok(error) => Err(Error::Api {
+                    status: code,
+                    error,
+                }),
+
+                Err(_) => Err(Error::UnexpectedNok {
+                    code,
+                    body: if body.is_empty() {
+                        None
+                    } else {
+                        Some(String::from_utf8_lossy(&body).to_string())
+                    },
+                }),
+            }
         }
     }
 
@@ -139,6 +266,45 @@ impl Client {
         .await
     }
 
+    pub async fn list_tasks(&self, query: &TaskQuery) -> Result<TasksPage> {
+        self.req::<Json<TasksPage>>(Method::GET, "/tasks", Query(query))
+            .await
+    }
+
+    /// Streams all tasks matching `query`, following the `next` cursor
+    pub fn stream_tasks(&self, query: TaskQuery) -> impl Stream<Item = Result<Task>> {
+        task_query::stream(self.clone(), query)
+    }
+
+    pub async fn get_batch(&self, batch_uid: u64) -> Result<Batch> {
+        self.req::<Json<Batch>>(Method::GET, &format!("/batches/{batch_uid}"), Empty)
+            .await
+    }
+
+    /// Lists batches containing tasks matching `query`
+    pub async fn list_batches(&self, query: &TaskQuery) -> Result<BatchesPage> {
+        self.req::<Json<BatchesPage>>(Method::GET, "/batches", Query(query))
+            .await
+    }
+
+    /// Cancels all enqueued or processing tasks matching `query`,
+    /// at least one filter is required. Limit and from are ignored.
+    pub async fn cancel_tasks(&self, query: &TaskQuery) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::POST,
+            "/tasks/cancel",
+            Query(&query.without_paging()),
+        )
+        .await
+    }
+
+    /// Deletes all finished tasks matching `query` from the task history,
+    /// at least one filter is required. Limit and from are ignored.
+    pub async fn delete_tasks(&self, query: &TaskQuery) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(Method::DELETE, "/tasks", Query(&query.without_paging()))
+            .await
+    }
+
     /// Searches index T
     pub async fn search<T>(
         &self,
@@ -149,7 +315,7 @@ impl Client {
         T: serde::de::DeserializeOwned,
     {
         let search = search.into();
-        self.req::<Json<SearchResponse<T>>>(
+        self.req_idempotent::<Json<SearchResponse<T>>>(
             Method::POST,
             &format!("/indexes/{}/search", index_uid.as_ref()),
             Json(&search),
@@ -157,6 +323,39 @@ impl Client {
         .await
     }
 
+    /// Sends several searches in a single request.
+    ///
+    /// Queries are either a `Vec<SearchQuery<T>>` or a tuple of
+    /// `SearchQuery`s with different hit types, see [`MultiSearchQueries`].
+    pub async fn multi_search<Q>(&self, queries: Q) -> Result<Q::Output>
+    where
+        Q: MultiSearchQueries,
+    {
+        self.req_idempotent::<Json<MultiSearchResponse<Q::Output>>>(
+            Method::POST,
+            "/multi-search",
+            Json(&MultiSearchRequest { queries }),
+        )
+        .await
+        .map(|res| res.results)
+    }
+
+    /// Searches several indexes and merges the hits into one ranked list.
+    pub async fn federated_search<T>(
+        &self,
+        search: &FederatedSearch,
+    ) -> Result<FederatedSearchResponse<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.req_idempotent::<Json<FederatedSearchResponse<T>>>(
+            Method::POST,
+            "/multi-search",
+            Json(search),
+        )
+        .await
+    }
+
     pub async fn add_or_replace_documents<T>(&self, docs: &[T]) -> Result<TaskRef>
     where
         T: HasIndex,
@@ -170,6 +369,103 @@ impl Client {
         .await
     }
 
+    /// Adds documents already serialized as `content_type`,
+    /// replacing (POST) or updating (PUT) existing ones.
+    pub(crate) async fn add_documents_raw(
+        &self,
+        method: Method,
+        index_uid: &str,
+        content_type: &'static str,
+        body: impl Into<reqwest::Body>,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            method,
+            &format!("/indexes/{index_uid}/documents"),
+            Raw {
+                content_type,
+                body: body.into(),
+            },
+        )
+        .await
+    }
+
+    /// Adds newline delimited JSON documents, replacing existing ones
+    pub async fn add_or_replace_documents_ndjson(
+        &self,
+        index_uid: impl AsRef<str>,
+        body: impl Into<DocumentsBody>,
+    ) -> Result<TaskRef> {
+        self.add_documents_raw(
+            Method::POST,
+            index_uid.as_ref(),
+            "application/x-ndjson",
+            body.into().0,
+        )
+        .await
+    }
+
+    /// Adds newline delimited JSON documents, updating existing ones
+    pub async fn add_or_update_documents_ndjson(
+        &self,
+        index_uid: impl AsRef<str>,
+        body: impl Into<DocumentsBody>,
+    ) -> Result<TaskRef> {
+        self.add_documents_raw(
+            Method::PUT,
+            index_uid.as_ref(),
+            "application/x-ndjson",
+            body.into().0,
+        )
+        .await
+    }
+
+    /// Adds CSV documents, replacing existing ones.
+    /// default delimiter `,`
+    pub async fn add_or_replace_documents_csv(
+        &self,
+        index_uid: impl AsRef<str>,
+        body: impl Into<DocumentsBody>,
+        delimiter: Option<char>,
+    ) -> Result<TaskRef> {
+        self.add_documents_csv(Method::POST, index_uid.as_ref(), body.into(), delimiter)
+            .await
+    }
+
+    /// Adds CSV documents, updating existing ones.
+    /// default delimiter `,`
+    pub async fn add_or_update_documents_csv(
+        &self,
+        index_uid: impl AsRef<str>,
+        body: impl Into<DocumentsBody>,
+        delimiter: Option<char>,
+    ) -> Result<TaskRef> {
+        self.add_documents_csv(Method::PUT, index_uid.as_ref(), body.into(), delimiter)
+            .await
+    }
+
+    async fn add_documents_csv(
+        &self,
+        method: Method,
+        index_uid: &str,
+        body: DocumentsBody,
+        delimiter: Option<char>,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            method,
+            &csv_documents_path(index_uid, delimiter),
+            Raw {
+                content_type: "text/csv",
+                body: body.0,
+            },
+        )
+        .await
+    }
+
+    /// Uploads documents in batches, see [`BulkIndexer`]
+    pub fn bulk_indexer(&self) -> BulkIndexer {
+        BulkIndexer::new(self)
+    }
+
     pub async fn add_or_replace_document<T>(&self, doc: &T) -> Result<TaskRef>
     where
         T: HasIndex,
@@ -209,6 +505,55 @@ impl Client {
         .await
     }
 
+    pub async fn get_document<T>(
+        &self,
+        index_uid: impl AsRef<str>,
+        document_uid: impl AsRef<str>,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.req::<Json<T>>(
+            Method::GET,
+            &format!(
+                "/indexes/{}/documents/{}",
+                index_uid.as_ref(),
+                document_uid.as_ref()
+            ),
+            Empty,
+        )
+        .await
+    }
+
+    pub async fn get_documents<T>(
+        &self,
+        index_uid: impl AsRef<str>,
+        query: &DocumentsQuery,
+    ) -> Result<DocumentsPage<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.req_idempotent::<Json<DocumentsPage<T>>>(
+            Method::POST,
+            &format!("/indexes/{}/documents/fetch", index_uid.as_ref()),
+            Json(query),
+        )
+        .await
+    }
+
+    /// Streams all documents matching `query`, see [`DocumentsQuery`].
+    /// The query's limit is used as page size, default 1000.
+    pub fn stream_documents<T>(
+        &self,
+        index_uid: impl Into<String>,
+        query: DocumentsQuery,
+    ) -> impl Stream<Item = Result<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        documents::stream(self.clone(), index_uid.into(), query)
+    }
+
     pub async fn delete_all_documents(&self, index_uid: impl AsRef<str>) -> Result<TaskRef> {
         self.req::<Json<TaskRef>>(
             Method::DELETE,
@@ -248,6 +593,51 @@ impl Client {
         .await
     }
 
+    /// Deletes all documents matching `filter`
+    pub async fn delete_documents_by_filter(
+        &self,
+        index_uid: impl AsRef<str>,
+        filter: impl Into<Filter>,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::POST,
+            &format!("/indexes/{}/documents/delete", index_uid.as_ref()),
+            Json(&serde_json::json!({ "filter": filter.into() })),
+        )
+        .await
+    }
+
+    /// Edits documents matching `filter`, or all documents, with a
+    /// [Rhai](https://rhai.rs) `function`. `context` is available to
+    /// the function as `context`.
+    pub async fn edit_documents_by_function(
+        &self,
+        index_uid: impl AsRef<str>,
+        filter: Option<Filter>,
+        function: impl Into<String>,
+        context: Option<serde_json::Value>,
+    ) -> Result<TaskRef> {
+        #[derive(serde::Serialize)]
+        struct Edit {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            filter: Option<Filter>,
+            function: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            context: Option<serde_json::Value>,
+        }
+
+        self.req::<Json<TaskRef>>(
+            Method::POST,
+            &format!("/indexes/{}/documents/edit", index_uid.as_ref()),
+            Json(&Edit {
+                filter,
+                function: function.into(),
+                context,
+            }),
+        )
+        .await
+    }
+
     pub async fn get_index(&self, index_uid: impl AsRef<str>) -> Result<Index> {
         self.req::<Json<Index>>(
             Method::GET,
@@ -257,6 +647,47 @@ impl Client {
         .await
     }
 
+    /// Lists indexes in ascending order of uid
+    pub async fn list_indexes(&self, offset: u32, limit: u32) -> Result<IndexesPage> {
+        self.req::<Json<IndexesPage>>(
+            Method::GET,
+            "/indexes",
+            Query(&[("offset", offset), ("limit", limit)]),
+        )
+        .await
+    }
+
+    /// Streams every index
+    pub fn stream_indexes(&self) -> impl Stream<Item = Result<Index>> {
+        index::stream(self.clone())
+    }
+
+    pub async fn get_index_stats(&self, index_uid: impl AsRef<str>) -> Result<IndexStats> {
+        self.req::<Json<IndexStats>>(
+            Method::GET,
+            &format!("/indexes/{}/stats", index_uid.as_ref()),
+            Empty,
+        )
+        .await
+    }
+
+    /// Sets the primary key of an index, which fails
+    /// if the index already contains documents.
+    pub async fn update_index_primary_key(
+        &self,
+        index_uid: impl AsRef<str>,
+        primary_key: impl AsRef<str>,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::PATCH,
+            &format!("/indexes/{}", index_uid.as_ref()),
+            Json(&serde_json::json!({
+                "primaryKey": primary_key.as_ref(),
+            })),
+        )
+        .await
+    }
+
     pub async fn get_index_settings(&self, index_uid: impl AsRef<str>) -> Result<IndexSettings> {
         self.req::<Json<_>>(
             Method::GET,
@@ -279,6 +710,65 @@ impl Client {
         .await
     }
 
+    /// Updates only the settings which are set in `patch`
+    pub async fn patch_index_settings(
+        &self,
+        index_uid: impl AsRef<str>,
+        patch: &SettingsPatch,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::PATCH,
+            &format!("/indexes/{}/settings", index_uid.as_ref()),
+            Json(patch),
+        )
+        .await
+    }
+
+    /// Resets all settings to their default values
+    pub async fn reset_index_settings(&self, index_uid: impl AsRef<str>) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::DELETE,
+            &format!("/indexes/{}/settings", index_uid.as_ref()),
+            Empty,
+        )
+        .await
+    }
+
+    /// Fetches a single setting, e.g `get_setting::<setting::Synonyms>("movies")`
+    pub async fn get_setting<S: Setting>(&self, index_uid: impl AsRef<str>) -> Result<S::Value> {
+        self.req::<Json<S::Value>>(
+            Method::GET,
+            &format!("/indexes/{}/settings/{}", index_uid.as_ref(), S::PATH),
+            Empty,
+        )
+        .await
+    }
+
+    /// Updates a single setting, settings updated with PATCH take a
+    /// patch and leave unset fields as they are
+    pub async fn update_setting<S: Setting>(
+        &self,
+        index_uid: impl AsRef<str>,
+        value: &S::Update,
+    ) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            S::UPDATE_METHOD,
+            &format!("/indexes/{}/settings/{}", index_uid.as_ref(), S::PATH),
+            Json(value),
+        )
+        .await
+    }
+
+    /// Resets a single setting to its default value
+    pub async fn reset_setting<S: Setting>(&self, index_uid: impl AsRef<str>) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(
+            Method::DELETE,
+            &format!("/indexes/{}/settings/{}", index_uid.as_ref(), S::PATH),
+            Empty,
+        )
+        .await
+    }
+
     pub async fn create_index(
         &self,
         index_uid: impl AsRef<str>,
@@ -304,77 +794,352 @@ impl Client {
         .await
     }
 
-    pub fn new(token: &str, url_s: &str, root_cert: Option<reqwest::Certificate>) -> Self {
-        let authorization_header = format!("Bearer {token}");
+    /// Swaps the documents, settings and task history of each pair
+    /// of indexes in a single atomic operation
+    pub async fn swap_indexes(&self, swaps: &[IndexSwap]) -> Result<TaskRef> {
+        self.req::<Json<TaskRef>>(Method::POST, "/swap-indexes", Json(&swaps))
+            .await
+    }
+
+    /// Returns a client sharing connections with this one but using
+    /// another retry policy, e.g to not retry latency sensitive searches.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        Self {
+            retry: Arc::new(policy),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client sharing connections with this one but using
+    /// other defaults for waiting on tasks.
+    #[cfg(feature = "tokio")]
+    pub fn with_wait_options(&self, opts: WaitOptions) -> Self {
+        Self {
+            wait_options: Arc::new(opts),
+            ..self.clone()
+        }
+    }
 
-        let mut builder = reqwest::Client::builder()
-            .default_headers(header::HeaderMap::from_iter([(
-                header::AUTHORIZATION,
-                header::HeaderValue::from_str(&authorization_header)
-                    .expect("token contained invalid values"),
-            )]))
-            .use_rustls_tls();
+    #[cfg(feature = "tokio")]
+    pub(crate) fn wait_options(&self) -> &WaitOptions {
+        &self.wait_options
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub fn new(
+        token: &str,
+        url_s: &str,
+        root_cert: Option<reqwest::Certificate>,
+    ) -> std::result::Result<Self, BuildError> {
+        let mut builder = ClientBuilder::new().api_key(token).base_url(url_s);
 
         if let Some(cert) = root_cert {
-            info!("adding root certificate");
+            builder = builder.root_certificate(cert);
+        }
 
-            builder = builder.add_root_certificate(cert);
-        } else {
-            info!("no root certificate added");
+        builder.build()
+    }
+
+    /// Waits for the task to stop using the client's [`WaitOptions`]
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_task(&self, task_uid: impl AsTaskUid) -> Result<Task> {
+        self.wait_for_task_with(task_uid, &self.wait_options.clone())
+            .await
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_task_with(
+        &self,
+        task_uid: impl AsTaskUid,
+        opts: &WaitOptions,
+    ) -> Result<Task> {
+        let uid = task_uid.as_task_uid();
+        let wait = self.wait_until_stopped(uid, opts);
+
+        let Some(timeout) = opts.get_timeout() else {
+            return wait.await;
+        };
+
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(res) => res,
+
+            // One last look, the task may have stopped since the last poll
+            Err(_) => {
+                let task = self.get_task(uid).await?;
+
+                if task.status.has_stopped() {
+                    #[cfg(feature = "hooks")]
+                    self.task_manager.handle_task(task.clone()).await;
+
+                    Ok(task)
+                } else {
+                    Err(Error::WaitTimeout {
+                        task_uid: uid,
+                        elapsed: timeout,
+                        last_status: Some(task.status),
+                    })
+                }
+            }
         }
+    }
 
-        let c = builder.build().expect("building client");
+    /// Waits for the task to be reported through [`TaskManager`]. If it has
+    /// not been reported after `poll_fallback_after` the task is also polled,
+    /// whichever finds the stopped task first wins. If the subscription
+    /// fails, e.g. because it was evicted, only polling is used.
+    #[cfg(all(feature = "tokio", feature = "hooks"))]
+    async fn wait_until_stopped(&self, uid: u64, opts: &WaitOptions) -> Result<Task> {
+        use futures::future::{self, Either};
+        use std::pin::pin;
 
-        Self {
-            c,
-            base_url: Arc::new(String::from(url_s)),
+        let hook = self.task_manager.subscribe_for_task(uid).await.wait();
+
+        let Some(delay) = opts.get_poll_fallback_after() else {
+            return match hook.await {
+                Ok(task) => Ok(task),
+                Err(err) => {
+                    warn!("meili: webhook wait for task {uid} failed, polling: {err}");
+                    self.poll_until_stopped(uid, opts).await
+                }
+            };
+        };
 
-            #[cfg(all(feature = "tokio", feature = "hooks"))]
-            task_manager: TaskManager::default(),
+        let poll = async move {
+            tokio::time::sleep(delay).await;
+
+            let task = self.poll_until_stopped(uid, opts).await?;
+            self.task_manager.handle_task(task.clone()).await;
+
+            Ok(task)
+        };
+
+        match future::select(pin!(hook), pin!(poll)).await {
+            Either::Left((Ok(task), _)) => Ok(task),
+            Either::Left((Err(err), poll)) => {
+                warn!("meili: webhook wait for task {uid} failed, polling: {err}");
+                poll.await
+            }
+            Either::Right((res, _)) => res,
+        }
+    }
+
+    #[cfg(all(feature = "tokio", not(feature = "hooks")))]
+    async fn wait_until_stopped(&self, uid: u64, opts: &WaitOptions) -> Result<Task> {
+        self.poll_until_stopped(uid, opts).await
+    }
+
+    /// Waits for all tasks to stop using the client's [`WaitOptions`]
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_tasks(
+        &self,
+        task_uids: impl IntoIterator<Item = impl AsTaskUid>,
+    ) -> Result<TasksReport> {
+        self.wait_for_tasks_with(task_uids, &self.wait_options.clone())
+            .await
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_tasks_with(
+        &self,
+        task_uids: impl IntoIterator<Item = impl AsTaskUid>,
+        opts: &WaitOptions,
+    ) -> Result<TasksReport> {
+        let mut pending = task_uids
+            .into_iter()
+            .map(|uid| uid.as_task_uid())
+            .collect::<BTreeSet<_>>();
+
+        let mut report = TasksReport::default();
+
+        let wait = self.wait_until_all_stopped(&mut pending, &mut report, opts);
+
+        match opts.get_timeout() {
+            Some(timeout) => match tokio::time::timeout(timeout, wait).await {
+                Ok(res) => res?,
+                Err(_) => {
+                    return Err(Error::WaitForTasksTimeout {
+                        pending: pending.into_iter().collect(),
+                        elapsed: timeout,
+                        report: Box::new(report),
+                    })
+                }
+            },
+            None => wait.await?,
         }
+
+        Ok(report)
     }
 
+    /// Waits for the tasks to be reported through [`TaskManager`]. Tasks
+    /// not reported after `poll_fallback_after`, or whose subscription
+    /// fails, are also polled with a single request per interval.
     #[cfg(all(feature = "tokio", feature = "hooks"))]
-    pub async fn wait_for_task(&self, task_uid: impl AsTaskUid) -> Result<Task> {
-        let uid = task_uid.as_task_uid();
-        let sub = self.task_manager.subscribe_for_task(uid).await;
+    async fn wait_until_all_stopped(
+        &self,
+        pending: &mut BTreeSet<u64>,
+        report: &mut TasksReport,
+        opts: &WaitOptions,
+    ) -> Result<()> {
+        use futures::{
+            future::{self, Either},
+            stream::FuturesUnordered,
+            StreamExt,
+        };
+        use std::pin::pin;
+        use tokio::time::{sleep_until, Instant};
+
+        let mut hooks = FuturesUnordered::new();
+        for uid in pending.iter() {
+            hooks.push(self.task_manager.subscribe_for_task(*uid).await.wait());
+        }
+
+        let mut intervals = opts.intervals();
+        let mut next_poll = opts
+            .get_poll_fallback_after()
+            .map(|delay| Instant::now() + delay);
+
+        while !pending.is_empty() {
+            let hook = async {
+                match hooks.next().await {
+                    Some(res) => res,
+                    None => future::pending().await,
+                }
+            };
+            let tick = async move {
+                match next_poll {
+                    Some(at) => sleep_until(at).await,
+                    None => future::pending().await,
+                }
+            };
+
+            let event = match future::select(pin!(hook), pin!(tick)).await {
+                Either::Left((res, _)) => Either::Left(res),
+                Either::Right(_) => Either::Right(()),
+            };
+
+            match event {
+                Either::Left(Ok(task)) => {
+                    if pending.remove(&task.uid) {
+                        report.record(task, opts.get_fail_fast())?;
+                    }
+                }
+
+                Either::Left(Err(err)) => {
+                    warn!("meili: webhook wait for tasks failed, polling: {err}");
+                    next_poll.get_or_insert_with(Instant::now);
+                }
+
+                Either::Right(_) => {
+                    for task in self.poll_pending(pending).await? {
+                        self.task_manager.handle_task(task.clone()).await;
+                        report.record(task, opts.get_fail_fast())?;
+                    }
+
+                    let interval = intervals.next().expect("poll intervals are infinite");
+                    next_poll = Some(Instant::now() + interval);
+                }
+            }
+        }
 
-        Ok(sub
-            .wait_with_timeout(tokio::time::Duration::from_secs(3))
-            .await?)
+        Ok(())
     }
 
+    /// Polls all pending tasks with a single request per interval
     #[cfg(all(feature = "tokio", not(feature = "hooks")))]
-    pub async fn wait_for_task(&self, task_uid: impl AsTaskUid) -> Result<Task> {
-        use std::time;
+    async fn wait_until_all_stopped(
+        &self,
+        pending: &mut BTreeSet<u64>,
+        report: &mut TasksReport,
+        opts: &WaitOptions,
+    ) -> Result<()> {
+        for interval in opts.intervals() {
+            if pending.is_empty() {
+                return Ok(());
+            }
 
-        let uid = task_uid.as_task_uid();
+            tokio::time::sleep(interval).await;
+
+            for task in self.poll_pending(pending).await? {
+                report.record(task, opts.get_fail_fast())?;
+            }
+        }
+
+        unreachable!("poll intervals are infinite")
+    }
+
+    /// Fetches all pending tasks in one request, removes and returns
+    /// those which have stopped
+    #[cfg(feature = "tokio")]
+    async fn poll_pending(&self, pending: &mut BTreeSet<u64>) -> Result<Vec<Task>> {
+        let query = TaskQuery::new()
+            .uids(pending.iter().copied())
+            .limit(Some(pending.len() as u32));
+
+        Ok(self
+            .list_tasks(&query)
+            .await?
+            .results
+            .into_iter()
+            .filter(|task| task.status.has_stopped() && pending.remove(&task.uid))
+            .collect())
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn poll_until_stopped(&self, uid: u64, opts: &WaitOptions) -> Result<Task> {
+        for interval in opts.intervals() {
+            tokio::time::sleep(interval).await;
 
-        loop {
-            tokio::time::sleep(time::Duration::from_millis(500)).await;
             let task = self.get_task(uid).await?;
             if task.status.has_stopped() {
                 return Ok(task);
             }
         }
+
+        unreachable!("poll intervals are infinite")
     }
 
-    /// Creates a client from environment variables:
-    ///
-    /// * MEILI_TOKEN
-    /// * MEILI_URL
-    /// * MEILI_ROOT_CERT (optional)
-    // @TODO move this into a separate function taking params and returning a Result
-    pub fn from_env() -> Self {
-        let token = env::var("MEILI_TOKEN").expect("environment varaible MEILI_TOKEN");
-        let url = env::var("MEILI_URL").expect("environment varaible MEILI_TOKEN");
-
-        let cert = env::var("MEILI_ROOT_CERT")
-            .ok()
-            .map(|cert| reqwest::Certificate::from_pem(cert.as_bytes()))
-            .transpose()
-            .expect("invalid certificate in `MEILI_ROOT_CERT`");
-
-        Self::new(&token, &url, cert)
+    /// Creates a client from environment variables, see [`ClientBuilder::from_env`]
+    pub fn from_env() -> std::result::Result<Self, BuildError> {
+        ClientBuilder::from_env()?.build()
+    }
+}
+
+/// Path of CSV document additions, the delimiter is always percent-encoded
+fn csv_documents_path(index_uid: &str, delimiter: Option<char>) -> String {
+    let mut path = format!("/indexes/{index_uid}/documents");
+
+    if let Some(delimiter) = delimiter {
+        path.push_str("?csvDelimiter=");
+        let mut buf = [0; 4];
+        for b in delimiter.encode_utf8(&mut buf).bytes() {
+            path.push_str(&format!("%{b:02X}"));
+        }
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn csv_delimiter_is_percent_encoded() {
+        assert_eq!(
+            csv_documents_path("users", Some(';')),
+            "/indexes/users/documents?csvDelimiter=%3B"
+        );
+        assert_eq!(
+            csv_documents_path("users", Some('\t')),
+            "/indexes/users/documents?csvDelimiter=%09"
+        );
+        assert_eq!(
+            csv_documents_path("users", None),
+            "/indexes/users/documents"
+        );
     }
 }
@@ -0,0 +1,236 @@
+use reqwest::header;
+use std::{env, sync::Arc, time::Duration};
+use tracing::info;
+
+use super::Client;
+use crate::RetryPolicy;
+#[cfg(feature = "tokio")]
+use crate::WaitOptions;
+
+#[cfg(feature = "hooks")]
+use crate::TaskManager;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("environment variable `{0}` is not set")]
+    MissingEnv(&'static str),
+
+    #[error("no base url given")]
+    MissingBaseUrl,
+
+    #[error("api key contains invalid characters")]
+    InvalidApiKey,
+
+    #[error("invalid root certificate: {0}")]
+    InvalidCertificate(reqwest::Error),
+
+    #[error("building http client: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Configures and builds a [`Client`].
+///
+/// Timeouts, user agent, proxy and root certificates are only applied
+/// when the builder creates the `reqwest::Client`, they are ignored if one
+/// is supplied through [`ClientBuilder::http_client`]. The api key and
+/// default headers are sent with every request either way.
+#[derive(Default)]
+pub struct ClientBuilder {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<reqwest::Proxy>,
+    headers: header::HeaderMap,
+    root_certificates: Vec<reqwest::Certificate>,
+    http_client: Option<reqwest::Client>,
+    retry_policy: RetryPolicy,
+    #[cfg(feature = "tokio")]
+    wait_options: WaitOptions,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder from environment variables:
+    ///
+    /// * MEILI_URL
+    /// * MEILI_TOKEN (optional, no API key when unset)
+    /// * MEILI_ROOT_CERT (optional)
+    pub fn from_env() -> Result<Self, BuildError> {
+        let url = env::var("MEILI_URL").map_err(|_| BuildError::MissingEnv("MEILI_URL"))?;
+
+        let mut builder = Self::new().base_url(url);
+
+        if let Ok(token) = env::var("MEILI_TOKEN") {
+            builder = builder.api_key(token);
+        }
+
+        if let Ok(cert) = env::var("MEILI_ROOT_CERT") {
+            let cert = reqwest::Certificate::from_pem(cert.as_bytes())
+                .map_err(BuildError::InvalidCertificate)?;
+            builder = builder.root_certificate(cert);
+        }
+
+        Ok(builder)
+    }
+
+    /// Key sent as bearer token, leave unset for instances without a master key
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    pub fn connect_timeout(mut self, dur: Duration) -> Self {
+        self.connect_timeout = Some(dur);
+        self
+    }
+
+    /// Timeout of a whole request, from connecting until the body is read
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = Some(dur);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Header sent with every request
+    pub fn default_header(mut self, name: header::HeaderName, value: header::HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn root_certificate(mut self, cert: reqwest::Certificate) -> Self {
+        self.root_certificates.push(cert);
+        self
+    }
+
+    /// Use an already configured `reqwest::Client`
+    pub fn http_client(mut self, c: reqwest::Client) -> Self {
+        self.http_client = Some(c);
+        self
+    }
+
+    /// default [`RetryPolicy::default`]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Used when waiting for tasks
+    /// default [`WaitOptions::default`]
+    #[cfg(feature = "tokio")]
+    pub fn wait_options(mut self, opts: WaitOptions) -> Self {
+        self.wait_options = opts;
+        self
+    }
+
+    pub fn build(self) -> Result<Client, BuildError> {
+        let base_url = self.base_url.ok_or(BuildError::MissingBaseUrl)?;
+
+        let mut headers = self.headers;
+        if let Some(key) = self.api_key {
+            let mut value = header::HeaderValue::from_str(&format!("Bearer {key}"))
+                .map_err(|_| BuildError::InvalidApiKey)?;
+            value.set_sensitive(true);
+            headers.insert(header::AUTHORIZATION, value);
+        }
+
+        let c = match self.http_client {
+            Some(c) => c,
+            None => {
+                let mut builder = reqwest::Client::builder().use_rustls_tls();
+
+                if let Some(dur) = self.connect_timeout {
+                    builder = builder.connect_timeout(dur);
+                }
+
+                if let Some(dur) = self.timeout {
+                    builder = builder.timeout(dur);
+                }
+
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+
+                if self.root_certificates.is_empty() {
+                    info!("no root certificate added");
+                }
+
+                for cert in self.root_certificates {
+                    info!("adding root certificate");
+                    builder = builder.add_root_certificate(cert);
+                }
+
+                builder.build()?
+            }
+        };
+
+        Ok(Client {
+            c,
+            base_url: Arc::new(base_url),
+            headers: Arc::new(headers),
+            retry: Arc::new(self.retry_policy),
+            #[cfg(feature = "tokio")]
+            wait_options: Arc::new(self.wait_options),
+
+            #[cfg(feature = "hooks")]
+            task_manager: TaskManager::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn build_requires_base_url() {
+        assert!(matches!(
+            ClientBuilder::new().api_key("key").build(),
+            Err(BuildError::MissingBaseUrl)
+        ));
+    }
+
+    #[test]
+    fn build_rejects_invalid_api_key() {
+        assert!(matches!(
+            ClientBuilder::new()
+                .base_url("http://localhost:7700")
+                .api_key("in\nvalid")
+                .build(),
+            Err(BuildError::InvalidApiKey)
+        ));
+    }
+
+    #[test]
+    fn build_without_api_key() {
+        let c = ClientBuilder::new()
+            .base_url("http://localhost:7700")
+            .build()
+            .expect("build");
+
+        assert!(!c.headers.contains_key(header::AUTHORIZATION));
+    }
+}
@@ -0,0 +1,168 @@
+//! Timestamps are RFC 3339 strings and task durations ISO 8601 strings
+//! under every feature, so enabling a feature never changes a field type.
+//!
+//! The `chrono` and `time` features add accessors parsing timestamps into
+//! `chrono::DateTime<Utc>` and `time::OffsetDateTime`, such as
+//! `Task::enqueued_at_chrono`, and [`TaskQuery`](crate::TaskQuery) filters
+//! taking them, such as `TaskQuery::after_enqueued_at_chrono`.
+
+use std::time::Duration;
+
+#[cfg(feature = "chrono")]
+pub(crate) fn to_chrono(ts: &str) -> crate::Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|err| invalid(ts, err))
+}
+
+#[cfg(feature = "time")]
+pub(crate) fn to_time(ts: &str) -> crate::Result<time::OffsetDateTime> {
+    time::OffsetDateTime::parse(ts, &time::format_description::well_known::Rfc3339)
+        .map_err(|err| invalid(ts, err))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn invalid(ts: &str, err: impl std::fmt::Display) -> crate::Error {
+    crate::Error::InvalidTimestamp {
+        value: String::from(ts),
+        reason: err.to_string(),
+    }
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) fn from_chrono(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+/// Years RFC 3339 cannot express are sent as they are, for
+/// meilisearch to reject
+#[cfg(feature = "time")]
+pub(crate) fn from_time(at: time::OffsetDateTime) -> String {
+    at.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| at.to_string())
+}
+
+/// Adds `chrono` and `time` accessors for timestamp fields, failing with
+/// [`Error::InvalidTimestamp`](crate::Error::InvalidTimestamp). Accessors
+/// of optional fields return None when the field is unset.
+macro_rules! timestamp_accessors {
+    (
+        $ty:ty {
+            $($field:ident => $chrono:ident, $time:ident;)*
+        }
+        $(optional { $($opt:ident => $opt_chrono:ident, $opt_time:ident;)+ })?
+    ) => {
+        impl $ty {
+            $(
+                #[doc = concat!("`", stringify!($field), "` as a `chrono` timestamp")]
+                #[cfg(feature = "chrono")]
+                pub fn $chrono(&self) -> crate::Result<chrono::DateTime<chrono::Utc>> {
+                    crate::datetime::to_chrono(&self.$field)
+                }
+
+                #[doc = concat!("`", stringify!($field), "` as a `time` timestamp")]
+                #[cfg(feature = "time")]
+                pub fn $time(&self) -> crate::Result<time::OffsetDateTime> {
+                    crate::datetime::to_time(&self.$field)
+                }
+            )*
+
+            $($(
+                #[doc = concat!("`", stringify!($opt), "` as a `chrono` timestamp")]
+                #[cfg(feature = "chrono")]
+                pub fn $opt_chrono(
+                    &self,
+                ) -> crate::Result<Option<chrono::DateTime<chrono::Utc>>> {
+                    self.$opt
+                        .as_deref()
+                        .map(crate::datetime::to_chrono)
+                        .transpose()
+                }
+
+                #[doc = concat!("`", stringify!($opt), "` as a `time` timestamp")]
+                #[cfg(feature = "time")]
+                pub fn $opt_time(&self) -> crate::Result<Option<time::OffsetDateTime>> {
+                    self.$opt.as_deref().map(crate::datetime::to_time).transpose()
+                }
+            )+)?
+        }
+    };
+}
+
+pub(crate) use timestamp_accessors;
+
+/// Time between two timestamps, None if either is unset or invalid
+/// or `to` is before `from`
+#[cfg(feature = "chrono")]
+pub(crate) fn elapsed(from: Option<&str>, to: Option<&str>) -> Option<Duration> {
+    (to_chrono(to?).ok()? - to_chrono(from?).ok()?)
+        .to_std()
+        .ok()
+}
+
+/// Time between two timestamps, None if either is unset or invalid
+/// or `to` is before `from`
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn elapsed(from: Option<&str>, to: Option<&str>) -> Option<Duration> {
+    (to_time(to?).ok()? - to_time(from?).ok()?).try_into().ok()
+}
+
+/// Parses the subset of ISO 8601 durations used by meilisearch,
+/// e.g `PT0.012S`, `PT1M30S` or `P1DT2H`.
+pub(crate) fn parse_iso8601_duration(s: &str) -> Option<Duration> {
+    let rest = s.strip_prefix('P')?;
+    let (date, time) = rest.split_once('T').unwrap_or((rest, ""));
+
+    let mut secs = 0f64;
+
+    for (part, units) in [
+        (date, &[('W', 604_800f64), ('D', 86_400f64)][..]),
+        (time, &[('H', 3_600f64), ('M', 60f64), ('S', 1f64)][..]),
+    ] {
+        let mut num = String::new();
+
+        for c in part.chars() {
+            match units.iter().find(|(unit, _)| *unit == c) {
+                Some((_, factor)) => {
+                    secs += num.parse::<f64>().ok()? * factor;
+                    num.clear();
+                }
+                None if c.is_ascii_digit() || c == '.' => num.push(c),
+                None => return None,
+            }
+        }
+
+        if !num.is_empty() {
+            return None;
+        }
+    }
+
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(
+            parse_iso8601_duration("PT0.012S"),
+            Some(Duration::from_millis(12))
+        );
+        assert_eq!(
+            parse_iso8601_duration("PT1M30S"),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_iso8601_duration("P1DT2H"),
+            Some(Duration::from_secs(93_600))
+        );
+        assert_eq!(parse_iso8601_duration("PT"), Some(Duration::ZERO));
+
+        assert_eq!(parse_iso8601_duration("0.012S"), None);
+        assert_eq!(parse_iso8601_duration("PT12"), None);
+        assert_eq!(parse_iso8601_duration("PT1X"), None);
+    }
+}
@@ -0,0 +1,234 @@
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+
+use crate::{filter, Client, Filter, HasIndex};
+
+const DEFAULT_STREAM_PAGE_SIZE: u32 = 1000;
+
+/// Selects which documents, and which of their fields, to fetch.
+#[derive(Debug, Default, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentsQuery {
+    /// Attributes to display in the returned documents
+    /// default: ["*"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<String>,
+
+    /// Only fetch documents matching the filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<Filter>,
+
+    /// Number of documents to skip
+    /// default 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+
+    /// Maximum number of documents returned
+    /// default 20
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+
+    /// Only fetch documents with these primary keys
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ids: Vec<String>,
+}
+
+impl DocumentsQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fields<S>(mut self, fields: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn filter<F>(mut self, f: Option<F>) -> Self
+    where
+        F: Into<Filter>,
+    {
+        self.filter = f.map(Into::into);
+        self
+    }
+
+    pub fn offset(mut self, n: Option<u32>) -> Self {
+        self.offset = n;
+        self
+    }
+
+    pub fn limit(mut self, n: Option<u32>) -> Self {
+        self.limit = n;
+        self
+    }
+
+    pub fn ids<S>(mut self, ids: impl IntoIterator<Item = S>) -> Self
+    where
+        S: ToString,
+    {
+        self.ids = ids.into_iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn check_filterable<T>(&self) -> crate::Result<()>
+    where
+        T: HasIndex,
+    {
+        filter::ensure_filterable::<T>(self.filter.as_ref())
+    }
+}
+
+/// Streams all documents matching `query`, fetching `limit`
+/// documents at a time. default limit 1000
+pub(crate) fn stream<T>(
+    client: Client,
+    index_uid: String,
+    query: DocumentsQuery,
+) -> impl Stream<Item = crate::Result<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let query = DocumentsQuery {
+        limit: Some(query.limit.unwrap_or(DEFAULT_STREAM_PAGE_SIZE)),
+        ..query
+    };
+
+    let pages = DocumentPages {
+        client,
+        index_uid,
+        query,
+        done: false,
+    };
+
+    stream::try_unfold(pages, DocumentPages::next::<T>)
+        .map_ok(|docs| stream::iter(docs).map(Ok))
+        .try_flatten()
+}
+
+struct DocumentPages {
+    client: Client,
+    index_uid: String,
+    query: DocumentsQuery,
+    done: bool,
+}
+
+impl DocumentPages {
+    async fn next<T>(mut self) -> crate::Result<Option<(Vec<T>, Self)>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page = self
+            .client
+            .get_documents::<T>(&self.index_uid, &self.query)
+            .await?;
+
+        let offset = page.offset + page.results.len() as u32;
+        self.done = page.results.is_empty() || page.total <= offset;
+        self.query.offset = Some(offset);
+
+        Ok(Some((page.results, self)))
+    }
+}
+
+/// Already serialized documents, e.g. NDJSON or CSV exports.
+pub struct DocumentsBody(pub(crate) reqwest::Body);
+
+impl DocumentsBody {
+    /// Reads all of `r` into memory
+    pub fn from_reader(mut r: impl std::io::Read) -> std::io::Result<Self> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf)?;
+        Ok(Self::from_bytes(buf))
+    }
+
+    pub fn from_bytes(bs: impl Into<bytes::Bytes>) -> Self {
+        Self(reqwest::Body::from(bs.into()))
+    }
+
+    /// Streams the body without buffering it. Streamed bodies are never retried.
+    pub fn from_stream<S>(s: S) -> Self
+    where
+        S: futures::TryStream + Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        bytes::Bytes: From<S::Ok>,
+    {
+        Self(reqwest::Body::wrap_stream(s))
+    }
+}
+
+impl From<Vec<u8>> for DocumentsBody {
+    fn from(bs: Vec<u8>) -> Self {
+        Self::from_bytes(bs)
+    }
+}
+
+impl From<bytes::Bytes> for DocumentsBody {
+    fn from(bs: bytes::Bytes) -> Self {
+        Self::from_bytes(bs)
+    }
+}
+
+impl From<String> for DocumentsBody {
+    fn from(s: String) -> Self {
+        Self::from_bytes(s)
+    }
+}
+
+impl From<&'static str> for DocumentsBody {
+    fn from(s: &'static str) -> Self {
+        Self::from_bytes(s)
+    }
+}
+
+impl From<&'static [u8]> for DocumentsBody {
+    fn from(bs: &'static [u8]) -> Self {
+        Self::from_bytes(bs)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentsPage<T> {
+    pub results: Vec<T>,
+
+    pub offset: u32,
+
+    pub limit: u32,
+
+    /// Total number of documents matching the query
+    pub total: u32,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn serialize_documents_query() {
+        let query = DocumentsQuery::new()
+            .fields(["id", "name"])
+            .filter(Some(Filter::eq("tenant_id", 3)))
+            .offset(Some(40))
+            .limit(Some(20));
+
+        assert_eq!(
+            serde_json::to_value(&query).expect("ser"),
+            serde_json::json!({
+                "fields": ["id", "name"],
+                "filter": "tenant_id = 3",
+                "offset": 40,
+                "limit": 20,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(DocumentsQuery::new()).expect("ser"),
+            serde_json::json!({})
+        );
+    }
+}
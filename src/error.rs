@@ -0,0 +1,212 @@
+use std::fmt;
+
+/// Error body returned by meilisearch for unsuccessful requests.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub message: String,
+    pub code: ErrorCode,
+    #[serde(rename = "type")]
+    pub error_type: ErrorType,
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $s:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)+
+
+            /// Value not known to this version of the crate
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $s,)+
+                    Self::Unknown(s) => s,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                match s.as_str() {
+                    $($s => Self::$variant,)+
+                    _ => Self::Unknown(s),
+                }
+            }
+        }
+
+        /// Empty unknown value
+        impl Default for $name {
+            fn default() -> Self {
+                Self::Unknown(String::new())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Error codes returned by meilisearch, shared by request and task errors.
+    pub enum ErrorCode {
+        ApiKeyAlreadyExists => "api_key_already_exists",
+        ApiKeyNotFound => "api_key_not_found",
+        BadRequest => "bad_request",
+        BatchNotFound => "batch_not_found",
+        DatabaseSizeLimitReached => "database_size_limit_reached",
+        DocumentFieldsLimitReached => "document_fields_limit_reached",
+        DocumentNotFound => "document_not_found",
+        DumpProcessFailed => "dump_process_failed",
+        IndexAlreadyExists => "index_already_exists",
+        IndexCreationFailed => "index_creation_failed",
+        IndexNotFound => "index_not_found",
+        IndexPrimaryKeyAlreadyExists => "index_primary_key_already_exists",
+        IndexPrimaryKeyMultipleCandidatesFound => "index_primary_key_multiple_candidates_found",
+        IndexPrimaryKeyNoCandidateFound => "index_primary_key_no_candidate_found",
+        Internal => "internal",
+        InvalidApiKey => "invalid_api_key",
+        InvalidContentType => "invalid_content_type",
+        InvalidDocumentFilter => "invalid_document_filter",
+        InvalidDocumentId => "invalid_document_id",
+        InvalidIndexPrimaryKey => "invalid_index_primary_key",
+        InvalidIndexUid => "invalid_index_uid",
+        InvalidSearchFilter => "invalid_search_filter",
+        InvalidSearchSort => "invalid_search_sort",
+        InvalidState => "invalid_state",
+        InvalidSwapIndexes => "invalid_swap_indexes",
+        IoError => "io_error",
+        MalformedPayload => "malformed_payload",
+        MaxFieldsLimitExceeded => "max_fields_limit_exceeded",
+        MissingAuthorizationHeader => "missing_authorization_header",
+        MissingContentType => "missing_content_type",
+        MissingDocumentId => "missing_document_id",
+        MissingMasterKey => "missing_master_key",
+        MissingPayload => "missing_payload",
+        MissingTaskFilters => "missing_task_filters",
+        NoSpaceLeftOnDevice => "no_space_left_on_device",
+        NotFound => "not_found",
+        PayloadTooLarge => "payload_too_large",
+        TaskNotFound => "task_not_found",
+        TooManyOpenFiles => "too_many_open_files",
+        TooManySearchRequests => "too_many_search_requests",
+        UnretrievableDocument => "unretrievable_document",
+        UnsupportedMediaType => "unsupported_media_type",
+    }
+}
+
+string_enum! {
+    pub enum ErrorType {
+        InvalidRequest => "invalid_request",
+        Internal => "internal",
+        Auth => "auth",
+        System => "system",
+    }
+}
+
+impl ErrorCode {
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::ApiKeyNotFound
+                | Self::BatchNotFound
+                | Self::DocumentNotFound
+                | Self::IndexNotFound
+                | Self::NotFound
+                | Self::TaskNotFound
+        )
+    }
+
+    /// Errors caused by load or exhausted resources on the server,
+    /// which may succeed if tried again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::TooManySearchRequests | Self::TooManyOpenFiles)
+    }
+
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidApiKey | Self::MissingAuthorizationHeader | Self::MissingMasterKey
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn deserialize_api_error() {
+        let s = r#"
+{"message":"Index `movies` not found.","code":"index_not_found","type":"invalid_request","link":"https://docs.meilisearch.com/errors#index_not_found"}"#;
+
+        let err = serde_json::from_str::<ApiError>(s).expect("deser");
+
+        assert_eq!(err.code, ErrorCode::IndexNotFound);
+        assert_eq!(err.error_type, ErrorType::InvalidRequest);
+        assert!(err.code.is_not_found());
+    }
+
+    #[test]
+    fn unknown_error_code_is_kept() {
+        let s = r#"{"message":"new","code":"brand_new_code","type":"brand_new_type","link":null}"#;
+
+        let err = serde_json::from_str::<ApiError>(s).expect("deser");
+
+        assert_eq!(err.code, ErrorCode::Unknown(String::from("brand_new_code")));
+        assert_eq!(err.code.as_str(), "brand_new_code");
+        assert_eq!(
+            serde_json::to_value(&err).expect("ser")["type"],
+            "brand_new_type"
+        );
+    }
+
+    #[test]
+    fn task_error_without_link() {
+        let s = r#"{"message":"boom","code":"internal","type":"internal"}"#;
+
+        let err = serde_json::from_str::<crate::TaskError>(s).expect("deser");
+
+        assert_eq!(err.link, None);
+        assert_eq!(crate::TaskError::default().code.as_str(), "");
+    }
+}
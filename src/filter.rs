@@ -0,0 +1,588 @@
+use std::fmt;
+
+use crate::{Error, HasIndex};
+
+/// A filter expression for [`crate::Search::filter`].
+///
+/// Values are escaped when rendered, so user supplied input
+/// can be passed as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Filter expression passed to meilisearch as is
+    Raw(String),
+
+    Compare {
+        attribute: String,
+        op: CompareOp,
+        value: FilterValue,
+    },
+
+    /// `attribute from TO to`, both ends inclusive
+    Range {
+        attribute: String,
+        from: FilterValue,
+        to: FilterValue,
+    },
+
+    In {
+        attribute: String,
+        values: Vec<FilterValue>,
+    },
+
+    Exists(String),
+    IsNull(String),
+    IsEmpty(String),
+
+    /// Documents within `distance` meters of a point
+    GeoRadius {
+        lat: f64,
+        lng: f64,
+        distance: f64,
+    },
+
+    /// Documents within a rectangle given by its top left and bottom right
+    /// corners as `(lat, lng)`
+    GeoBoundingBox {
+        top_left: (f64, f64),
+        bottom_right: (f64, f64),
+    },
+
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl CompareOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Gte => ">=",
+            Self::Lt => "<",
+            Self::Lte => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    String(String),
+    Integer(i64),
+    /// Unsigned integers, which may not fit in an `i64`
+    UInteger(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    if matches!(c, '"' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                f.write_str("\"")
+            }
+            Self::Integer(n) => write!(f, "{n}"),
+            Self::UInteger(n) => write!(f, "{n}"),
+            Self::Float(n) => write!(f, "{n}"),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(s: &str) -> Self {
+        Self::String(String::from(s))
+    }
+}
+
+impl From<&String> for FilterValue {
+    fn from(s: &String) -> Self {
+        Self::String(s.clone())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<f32> for FilterValue {
+    fn from(n: f32) -> Self {
+        Self::Float(n.into())
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(n: f64) -> Self {
+        Self::Float(n)
+    }
+}
+
+macro_rules! impl_from_integer_for_filter_value {
+    ($variant:ident as $as:ty: $($t:ty),+) => {
+        $(
+            impl From<$t> for FilterValue {
+                fn from(n: $t) -> Self {
+                    Self::$variant(n as $as)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_integer_for_filter_value!(Integer as i64: i8, i16, i32, i64, isize);
+impl_from_integer_for_filter_value!(UInteger as u64: u8, u16, u32, u64, usize);
+
+impl Filter {
+    fn compare(attribute: impl Into<String>, op: CompareOp, value: impl Into<FilterValue>) -> Self {
+        Self::Compare {
+            attribute: attribute.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Eq, value)
+    }
+
+    pub fn ne(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Ne, value)
+    }
+
+    pub fn gt(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Gt, value)
+    }
+
+    pub fn gte(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Gte, value)
+    }
+
+    pub fn lt(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Lt, value)
+    }
+
+    pub fn lte(attribute: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(attribute, CompareOp::Lte, value)
+    }
+
+    pub fn range(
+        attribute: impl Into<String>,
+        from: impl Into<FilterValue>,
+        to: impl Into<FilterValue>,
+    ) -> Self {
+        Self::Range {
+            attribute: attribute.into(),
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn is_in<V>(attribute: impl Into<String>, values: impl IntoIterator<Item = V>) -> Self
+    where
+        V: Into<FilterValue>,
+    {
+        Self::In {
+            attribute: attribute.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn exists(attribute: impl Into<String>) -> Self {
+        Self::Exists(attribute.into())
+    }
+
+    pub fn is_null(attribute: impl Into<String>) -> Self {
+        Self::IsNull(attribute.into())
+    }
+
+    pub fn is_empty(attribute: impl Into<String>) -> Self {
+        Self::IsEmpty(attribute.into())
+    }
+
+    pub fn geo_radius(lat: f64, lng: f64, distance: f64) -> Self {
+        Self::GeoRadius { lat, lng, distance }
+    }
+
+    pub fn geo_bounding_box(top_left: (f64, f64), bottom_right: (f64, f64)) -> Self {
+        Self::GeoBoundingBox {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// Matches when all filters match, an empty group renders
+    /// as nothing and matches everything
+    pub fn all(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Self::And(filters.into_iter().collect())
+    }
+
+    /// Matches when any of the filters match, an empty group renders
+    /// as nothing and matches everything
+    pub fn any(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Self::Or(filters.into_iter().collect())
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Self::And(mut filters) => {
+                filters.push(other);
+                Self::And(filters)
+            }
+            f => Self::And(vec![f, other]),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Self::Or(mut filters) => {
+                filters.push(other);
+                Self::Or(filters)
+            }
+            f => Self::Or(vec![f, other]),
+        }
+    }
+
+    /// Attributes referenced by this filter. Raw filters are not parsed
+    /// and do not contribute any attributes.
+    pub fn attributes(&self) -> Vec<&str> {
+        let mut attrs = Vec::new();
+        self.collect_attributes(&mut attrs);
+        attrs
+    }
+
+    fn collect_attributes<'a>(&'a self, attrs: &mut Vec<&'a str>) {
+        match self {
+            Self::Raw(_) => (),
+
+            Self::Compare { attribute, .. }
+            | Self::Range { attribute, .. }
+            | Self::In { attribute, .. }
+            | Self::Exists(attribute)
+            | Self::IsNull(attribute)
+            | Self::IsEmpty(attribute) => attrs.push(attribute),
+
+            Self::GeoRadius { .. } | Self::GeoBoundingBox { .. } => attrs.push("_geo"),
+
+            Self::And(filters) | Self::Or(filters) => {
+                for f in filters {
+                    f.collect_attributes(attrs);
+                }
+            }
+
+            Self::Not(f) => f.collect_attributes(attrs),
+        }
+    }
+
+    /// Checks that every attribute of the filter is filterable in the index of `T`.
+    ///
+    /// Returns the first attribute which is not.
+    pub fn check_filterable<T>(&self) -> Result<(), String>
+    where
+        T: HasIndex,
+    {
+        match self
+            .attributes()
+            .into_iter()
+            .find(|attr| !is_declared(T::FILTERABLE_ATTRIBUTES, attr))
+        {
+            Some(attr) => Err(String::from(attr)),
+            None => Ok(()),
+        }
+    }
+
+    /// True for groups which render as nothing, such as `Filter::all([])`
+    fn is_void(&self) -> bool {
+        match self {
+            Self::And(filters) | Self::Or(filters) => filters.iter().all(Filter::is_void),
+            Self::Not(f) => f.is_void(),
+            _ => false,
+        }
+    }
+
+    /// First NaN or infinite number of the filter, which has no
+    /// representation in a filter expression
+    fn non_finite(&self) -> Option<f64> {
+        let values = match self {
+            Self::Compare { value, .. } => vec![value],
+            Self::Range { from, to, .. } => vec![from, to],
+            Self::In { values, .. } => values.iter().collect(),
+
+            Self::GeoRadius { lat, lng, distance } => {
+                return [*lat, *lng, *distance].into_iter().find(|n| !n.is_finite())
+            }
+            Self::GeoBoundingBox {
+                top_left: (tl_lat, tl_lng),
+                bottom_right: (br_lat, br_lng),
+            } => {
+                return [*tl_lat, *tl_lng, *br_lat, *br_lng]
+                    .into_iter()
+                    .find(|n| !n.is_finite())
+            }
+
+            Self::And(filters) | Self::Or(filters) => {
+                return filters.iter().find_map(Filter::non_finite)
+            }
+            Self::Not(f) => return f.non_finite(),
+
+            Self::Raw(_) | Self::Exists(_) | Self::IsNull(_) | Self::IsEmpty(_) => return None,
+        };
+
+        values.into_iter().find_map(|v| match v {
+            FilterValue::Float(n) if !n.is_finite() => Some(*n),
+            _ => None,
+        })
+    }
+}
+
+/// Fails with [`Error::UnfilterableAttribute`] if `filter` references
+/// attributes which are not filterable in the index of `T`.
+pub(crate) fn ensure_filterable<T>(filter: Option<&Filter>) -> crate::Result<()>
+where
+    T: HasIndex,
+{
+    match filter.map(Filter::check_filterable::<T>) {
+        Some(Err(attribute)) => Err(Error::UnfilterableAttribute {
+            index_uid: T::INDEX_UID,
+            attribute,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Declared filterable or sortable attributes also cover their nested
+/// fields, i.e `sites` makes `sites.id` filterable.
+pub(crate) fn is_declared(declared: &[&str], attribute: &str) -> bool {
+    declared.iter().any(|f| {
+        *f == "*"
+            || *f == attribute
+            || attribute
+                .strip_prefix(f)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Raw(s) => f.write_str(s),
+
+            Self::Compare {
+                attribute,
+                op,
+                value,
+            } => write!(f, "{attribute} {} {value}", op.as_str()),
+
+            Self::Range {
+                attribute,
+                from,
+                to,
+            } => write!(f, "{attribute} {from} TO {to}"),
+
+            Self::In { attribute, values } => {
+                write!(f, "{attribute} IN [")?;
+                for (i, v) in values.iter().enumerate() {
+                    if 0 < i {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_str("]")
+            }
+
+            Self::Exists(attribute) => write!(f, "{attribute} EXISTS"),
+            Self::IsNull(attribute) => write!(f, "{attribute} IS NULL"),
+            Self::IsEmpty(attribute) => write!(f, "{attribute} IS EMPTY"),
+
+            Self::GeoRadius { lat, lng, distance } => {
+                write!(f, "_geoRadius({lat}, {lng}, {distance})")
+            }
+
+            Self::GeoBoundingBox {
+                top_left: (tl_lat, tl_lng),
+                bottom_right: (br_lat, br_lng),
+            } => write!(
+                f,
+                "_geoBoundingBox([{tl_lat}, {tl_lng}], [{br_lat}, {br_lng}])"
+            ),
+
+            Self::And(filters) => write_joined(f, filters, " AND "),
+            Self::Or(filters) => write_joined(f, filters, " OR "),
+
+            Self::Not(inner) if inner.is_void() => Ok(()),
+            Self::Not(inner) => write!(f, "NOT ({inner})"),
+        }
+    }
+}
+
+fn write_joined(f: &mut fmt::Formatter<'_>, filters: &[Filter], sep: &str) -> fmt::Result {
+    let mut filters = filters.iter().filter(|filter| !filter.is_void()).peekable();
+    if filters.peek().is_none() {
+        return Ok(());
+    }
+
+    f.write_str("(")?;
+    for (i, filter) in filters.enumerate() {
+        if 0 < i {
+            f.write_str(sep)?;
+        }
+        write!(f, "{filter}")?;
+    }
+    f.write_str(")")
+}
+
+impl serde::Serialize for Filter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if let Some(n) = self.non_finite() {
+            return Err(serde::ser::Error::custom(format!(
+                "filter contains the non-finite number {n}"
+            )));
+        }
+
+        serializer.collect_str(self)
+    }
+}
+
+impl From<&str> for Filter {
+    fn from(s: &str) -> Self {
+        Self::Raw(String::from(s))
+    }
+}
+
+impl From<&String> for Filter {
+    fn from(s: &String) -> Self {
+        Self::Raw(s.clone())
+    }
+}
+
+impl From<String> for Filter {
+    fn from(s: String) -> Self {
+        Self::Raw(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn render_escaped_values() {
+        let f = Filter::eq("name", r#"Robert "); DROP"#).or(Filter::ne("path", r"C:\"));
+
+        assert_eq!(
+            f.to_string(),
+            r#"(name = "Robert \"); DROP" OR path != "C:\\")"#
+        );
+    }
+
+    #[test]
+    fn render_operators() {
+        let f = Filter::all([
+            Filter::range("price", 10, 20.5),
+            Filter::exists("color"),
+            !Filter::is_null("size"),
+            Filter::is_empty("tags"),
+            Filter::geo_radius(45.47, 9.18, 2000.0),
+            Filter::geo_bounding_box((45.49, 9.17), (45.46, 9.2)),
+        ]);
+
+        assert_eq!(
+            f.to_string(),
+            "(price 10 TO 20.5 AND color EXISTS AND NOT (size IS NULL) AND tags IS EMPTY \
+             AND _geoRadius(45.47, 9.18, 2000) \
+             AND _geoBoundingBox([45.49, 9.17], [45.46, 9.2]))"
+        );
+    }
+
+    #[test]
+    fn check_filterable_attributes() {
+        struct Doc;
+
+        impl HasIndex for Doc {
+            const INDEX_UID: &'static str = "docs";
+            const PRIMARY_KEY: &'static str = "id";
+            const FILTERABLE_ATTRIBUTES: &'static [&'static str] = &["sites", "name"];
+        }
+
+        assert!(Filter::eq("sites.id", 23)
+            .and(Filter::eq("name", "Adam"))
+            .check_filterable::<Doc>()
+            .is_ok());
+
+        assert_eq!(
+            Filter::eq("sitesx", 1).check_filterable::<Doc>(),
+            Err(String::from("sitesx"))
+        );
+
+        assert_eq!(
+            Filter::geo_radius(1.0, 1.0, 1.0).check_filterable::<Doc>(),
+            Err(String::from("_geo"))
+        );
+
+        assert!(Filter::from("whatever = 1")
+            .check_filterable::<Doc>()
+            .is_ok());
+    }
+
+    #[test]
+    fn large_unsigned_values_do_not_wrap() {
+        assert_eq!(
+            Filter::eq("id", u64::MAX).to_string(),
+            "id = 18446744073709551615"
+        );
+        assert_eq!(Filter::eq("id", -3i8).to_string(), "id = -3");
+    }
+
+    #[test]
+    fn empty_groups_render_as_nothing() {
+        assert_eq!(Filter::all([]).to_string(), "");
+        assert_eq!((!Filter::any([])).to_string(), "");
+        assert_eq!(
+            Filter::all([Filter::any([]), Filter::exists("color")]).to_string(),
+            "(color EXISTS)"
+        );
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() {
+        assert!(serde_json::to_string(&Filter::gt("price", f64::NAN)).is_err());
+        assert!(serde_json::to_string(&Filter::geo_radius(1.0, f64::INFINITY, 10.0)).is_err());
+        assert!(serde_json::to_string(&Filter::gt("price", 1.5)).is_ok());
+    }
+}
@@ -1,9 +1,26 @@
+use futures::Stream;
+use std::collections::HashMap;
+#[cfg(feature = "tokio")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "tokio")]
+use tracing::warn;
+
+#[cfg(feature = "tokio")]
+use crate::{
+    bulk::BulkIndexer,
+    task::{IndexSwap, Task, TaskStatus},
+    Error, WaitOptions,
+};
 use crate::{
     client::Client,
-    index::Index,
+    documents::{DocumentsPage, DocumentsQuery},
+    filter::{self, Filter},
+    index::{Faceting, Index, IndexStats, Pagination, TypoTolerance},
+    multi_search::SearchQuery,
     search::{Search, SearchResponse},
+    sort,
     task::TaskRef,
-    Error, IndexSettings, Result,
+    ErrorCode, IndexSettings, RankingRule, Result, SettingsPatch,
 };
 
 #[allow(async_fn_in_trait)]
@@ -19,6 +36,36 @@ where
     const FILTERABLE_ATTRIBUTES: &'static [&'static str] = &[];
     const SORTABLE_ATTRIBUTES: &'static [&'static str] = &[];
 
+    // Settings below are left as they are in meilisearch when None
+
+    /// In order of importance, e.g `["words", "typo", "release_date:desc"]`,
+    /// attributes of custom rules must be in `SORTABLE_ATTRIBUTES`.
+    const RANKING_RULES: Option<&'static [&'static str]> = None;
+    const DISPLAYED_ATTRIBUTES: Option<&'static [&'static str]> = None;
+    const DISTINCT_ATTRIBUTE: Option<&'static str> = None;
+    const STOP_WORDS: Option<&'static [&'static str]> = None;
+    const SEPARATOR_TOKENS: Option<&'static [&'static str]> = None;
+    const NON_SEPARATOR_TOKENS: Option<&'static [&'static str]> = None;
+    const DICTIONARY: Option<&'static [&'static str]> = None;
+    /// `byWord` or `byAttribute`
+    const PROXIMITY_PRECISION: Option<&'static str> = None;
+
+    fn synonyms() -> Option<HashMap<String, Vec<String>>> {
+        None
+    }
+
+    fn typo_tolerance() -> Option<TypoTolerance> {
+        None
+    }
+
+    fn faceting() -> Option<Faceting> {
+        None
+    }
+
+    fn pagination() -> Option<Pagination> {
+        None
+    }
+
     async fn replace_in_index(&self, c: &Client) -> Result<TaskRef>
     where
         Self: serde::Serialize,
@@ -33,21 +80,74 @@ where
         c.add_or_update_document(self).await
     }
 
+    /// Searches the index, filters and sorts are checked against
+    /// `FILTERABLE_ATTRIBUTES` and `SORTABLE_ATTRIBUTES` before
+    /// sending the request.
     async fn search(c: &Client, search: impl Into<Search>) -> Result<SearchResponse<Self>>
     where
         Self: serde::de::DeserializeOwned,
     {
+        let search = search.into();
+        search.check_filterable::<Self>()?;
+        search.check_sortable::<Self>()?;
         c.search(Self::INDEX_UID, search).await
     }
 
+    /// Fetches the document with primary key `document_uid`
+    async fn get(c: &Client, document_uid: impl AsRef<str>) -> Result<Self>
+    where
+        Self: serde::de::DeserializeOwned,
+    {
+        c.get_document(Self::INDEX_UID, document_uid).await
+    }
+
+    /// Fetches a page of documents, filters are checked against
+    /// `FILTERABLE_ATTRIBUTES` before sending the request.
+    async fn list(c: &Client, query: &DocumentsQuery) -> Result<DocumentsPage<Self>>
+    where
+        Self: serde::de::DeserializeOwned,
+    {
+        query.check_filterable::<Self>()?;
+        c.get_documents(Self::INDEX_UID, query).await
+    }
+
+    /// Streams every document of the index
+    fn stream_all(c: &Client) -> impl Stream<Item = Result<Self>>
+    where
+        Self: serde::de::DeserializeOwned,
+    {
+        c.stream_documents(Self::INDEX_UID, DocumentsQuery::new())
+    }
+
+    /// Creates a query against this index, to be used with [`Client::multi_search`].
+    fn search_query(search: impl Into<Search>) -> SearchQuery<Self> {
+        SearchQuery::for_index(search)
+    }
+
     async fn get_index(c: &Client) -> Result<Index> {
         c.get_index(Self::INDEX_UID).await
     }
 
+    async fn stats(c: &Client) -> Result<IndexStats> {
+        c.get_index_stats(Self::INDEX_UID).await
+    }
+
+    /// Sets the primary key of the index to `PRIMARY_KEY`
+    async fn update_primary_key(c: &Client) -> Result<TaskRef> {
+        c.update_index_primary_key(Self::INDEX_UID, Self::PRIMARY_KEY)
+            .await
+    }
+
     async fn get_index_settings(c: &Client) -> Result<IndexSettings> {
         c.get_index_settings(Self::INDEX_UID).await
     }
 
+    /// Updates only the settings which are set in `patch`, settings
+    /// declared on the type are overwritten by `ensure_index_settings`.
+    async fn patch_index_settings(c: &Client, patch: &SettingsPatch) -> Result<TaskRef> {
+        c.patch_index_settings(Self::INDEX_UID, patch).await
+    }
+
     async fn create_index(c: &Client) -> Result<TaskRef> {
         c.create_index(Self::INDEX_UID, Self::PRIMARY_KEY).await
     }
@@ -63,49 +163,36 @@ where
         c.delete_documents(Self::INDEX_UID, document_uids).await
     }
 
+    /// Deletes all documents matching `filter`, which is checked
+    /// against `FILTERABLE_ATTRIBUTES` before sending the request.
+    async fn delete_documents_by_filter(c: &Client, filter: impl Into<Filter>) -> Result<TaskRef> {
+        let filter = filter.into();
+        filter::ensure_filterable::<Self>(Some(&filter))?;
+        c.delete_documents_by_filter(Self::INDEX_UID, filter).await
+    }
+
+    async fn edit_documents_by_function(
+        c: &Client,
+        filter: Option<Filter>,
+        function: impl Into<String>,
+        context: Option<serde_json::Value>,
+    ) -> Result<TaskRef> {
+        filter::ensure_filterable::<Self>(filter.as_ref())?;
+        c.edit_documents_by_function(Self::INDEX_UID, filter, function, context)
+            .await
+    }
+
     async fn delete_all_documents(c: &Client) -> Result<TaskRef> {
         c.delete_all_documents(Self::INDEX_UID).await
     }
 
     async fn ensure_index_settings(c: &Client) -> Result<()> {
-        let mut settings = Self::get_index_settings(c).await?;
-
-        let mut update_needed = false;
-
-        for (local, remote) in [
-            (
-                Self::FILTERABLE_ATTRIBUTES,
-                &mut settings.filterable_attributes,
-            ),
-            (Self::SORTABLE_ATTRIBUTES, &mut settings.sortable_attributes),
-            (
-                Self::SEARCHABLE_ATTRIBUTES,
-                &mut settings.searchable_attributes,
-            ),
-        ] {
-            let mut l = local.iter().map(|s| String::from(*s)).collect::<Vec<_>>();
-            l.sort();
-            remote.sort();
-
-            if &l != remote {
-                update_needed = true;
-                *remote = l;
-            }
-        }
-
-        if update_needed {
-            #[allow(unused_variables)]
-            let task_ref = c.update_index_settings(Self::INDEX_UID, &settings).await?;
-            #[cfg(any(feature = "tokio", feature = "hooks"))]
-            task_ref.wait_until_stopped(c).await?;
-        }
-
-        Ok(())
+        ensure_settings::<Self>(c, Self::INDEX_UID).await
     }
 
     async fn ensure_index(c: &Client) -> Result<()> {
         match Self::get_index(c).await {
-            Err(Error::UnexpectedNok { code: 404, .. }) => {
+            Err(err) if err.code() == Some(&ErrorCode::IndexNotFound) => {
                 #[allow(unused_variables)]
                 let task = Self::create_index(c).await?;
 
@@ -122,6 +209,214 @@ where
 
         Ok(())
     }
+
+    /// Rebuilds the index from `docs` without leaving search empty.
+    ///
+    /// Documents are loaded into a shadow index `{INDEX_UID}_tmp_<ts>` with
+    /// the same settings, which is then swapped with the live index and
+    /// deleted. If anything fails before the swap the shadow index is
+    /// deleted and the live index is left untouched. Failing to delete the
+    /// old documents after the swap is only logged.
+    ///
+    /// The shadow index gets the live settings, including those not
+    /// declared on the type, except embedder API keys which meilisearch
+    /// only returns redacted.
+    ///
+    /// Waits without a timeout, see [`HasIndex::reindex_with_options`].
+    #[cfg(feature = "tokio")]
+    async fn reindex_with(c: &Client, docs: impl IntoIterator<Item = Self>) -> Result<()>
+    where
+        Self: serde::Serialize,
+    {
+        let opts = c.wait_options().clone().timeout(None);
+        Self::reindex_with_options(c, docs, &opts).await
+    }
+
+    /// Like [`HasIndex::reindex_with`], waiting for the shadow index, the
+    /// documents and the swap with `opts`. On timeout the shadow index is
+    /// deleted.
+    #[cfg(feature = "tokio")]
+    async fn reindex_with_options(
+        c: &Client,
+        docs: impl IntoIterator<Item = Self>,
+        opts: &WaitOptions,
+    ) -> Result<()>
+    where
+        Self: serde::Serialize,
+    {
+        Self::ensure_index(c).await?;
+
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let shadow_uid = format!("{}_tmp_{ts}", Self::INDEX_UID);
+
+        let task = c.create_index(&shadow_uid, Self::PRIMARY_KEY).await?;
+
+        // From here on the shadow index may exist and is deleted on failure
+        let load_and_swap = async {
+            succeeded(task.wait_with(c, opts).await?)?;
+
+            let live = c.get_index_settings(Self::INDEX_UID).await?;
+            let task = c.update_index_settings(&shadow_uid, &live).await?;
+            succeeded(task.wait_with(c, opts).await?)?;
+            ensure_settings::<Self>(c, &shadow_uid).await?;
+
+            BulkIndexer::new(c)
+                .index_uid(&shadow_uid)
+                .wait_with(opts.clone())
+                .index(docs)
+                .await?;
+
+            let task = c
+                .swap_indexes(&[IndexSwap::new(Self::INDEX_UID, &shadow_uid)])
+                .await?;
+            succeeded(task.wait_with(c, opts).await?)
+        };
+
+        if let Err(err) = load_and_swap.await {
+            warn!(
+                "meili: reindexing `{}` failed, deleting `{shadow_uid}`: {err}",
+                Self::INDEX_UID
+            );
+
+            match c.delete_index(&shadow_uid).await {
+                Ok(task) => {
+                    if let Err(err) = task.wait_until_stopped(c).await {
+                        warn!("meili: waiting for deletion of `{shadow_uid}`: {err}");
+                    }
+                }
+                Err(err) => warn!("meili: deleting `{shadow_uid}`: {err}"),
+            }
+
+            return Err(err);
+        }
+
+        // The shadow index now holds the old documents, the reindex
+        // itself has succeeded even if it cannot be deleted
+        let delete_old = async {
+            let task = c.delete_index(&shadow_uid).await?;
+            succeeded(task.wait_until_stopped(c).await?)
+        };
+
+        if let Err(err) = delete_old.await {
+            warn!("meili: deleting old documents in `{shadow_uid}`: {err}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Makes the settings of `index_uid` match those declared by `T`
+pub(crate) async fn ensure_settings<T: HasIndex>(c: &Client, index_uid: &str) -> Result<()> {
+    if let Some(rules) = T::RANKING_RULES {
+        let rules = rules
+            .iter()
+            .map(|r| RankingRule::from(*r))
+            .collect::<Vec<_>>();
+        sort::ensure_sortable::<T>(rules.iter().filter_map(RankingRule::attribute))?;
+    }
+
+    let mut remote = c.get_index_settings(index_uid).await?;
+    normalize(&mut remote);
+
+    let mut local = declared_settings::<T>(remote.clone());
+    normalize(&mut local);
+
+    let patch = SettingsPatch::diff(&remote, &local);
+
+    if !patch.is_empty() {
+        #[allow(unused_variables)]
+        let task_ref = c.patch_index_settings(index_uid, &patch).await?;
+        #[cfg(any(feature = "tokio", feature = "hooks"))]
+        task_ref.wait_until_stopped(c).await?;
+    }
+
+    Ok(())
+}
+
+/// Overrides the settings declared by `T` in `settings`
+fn declared_settings<T: HasIndex>(mut settings: IndexSettings) -> IndexSettings {
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| String::from(*s)).collect()
+    }
+
+    settings.filterable_attributes = strings(T::FILTERABLE_ATTRIBUTES);
+    settings.sortable_attributes = strings(T::SORTABLE_ATTRIBUTES);
+    settings.searchable_attributes = strings(T::SEARCHABLE_ATTRIBUTES);
+
+    if let Some(rules) = T::RANKING_RULES {
+        settings.ranking_rules = rules.iter().map(|r| RankingRule::from(*r)).collect();
+    }
+
+    for (local, remote) in [
+        (T::DISPLAYED_ATTRIBUTES, &mut settings.displayed_attributes),
+        (T::STOP_WORDS, &mut settings.stop_words),
+        (T::SEPARATOR_TOKENS, &mut settings.separator_tokens),
+        (T::NON_SEPARATOR_TOKENS, &mut settings.non_separator_tokens),
+        (T::DICTIONARY, &mut settings.dictionary),
+    ] {
+        if let Some(local) = local {
+            *remote = strings(local);
+        }
+    }
+
+    if let Some(distinct) = T::DISTINCT_ATTRIBUTE {
+        settings.distinct_attribute = Some(String::from(distinct));
+    }
+
+    if let Some(precision) = T::PROXIMITY_PRECISION {
+        settings.proximity_precision = String::from(precision);
+    }
+
+    if let Some(synonyms) = T::synonyms() {
+        settings.synonyms = synonyms;
+    }
+
+    if let Some(typo_tolerance) = T::typo_tolerance() {
+        settings.typo_tolerance = typo_tolerance;
+    }
+
+    if let Some(faceting) = T::faceting() {
+        settings.faceting = faceting;
+    }
+
+    if let Some(pagination) = T::pagination() {
+        settings.pagination = pagination;
+    }
+
+    settings
+}
+
+/// Sorts settings where order is not significant, so they can be compared.
+/// Searchable and displayed attributes are kept in their declared order,
+/// which sets the priority of searchable attributes.
+fn normalize(settings: &mut IndexSettings) {
+    for v in [
+        &mut settings.filterable_attributes,
+        &mut settings.sortable_attributes,
+        &mut settings.stop_words,
+        &mut settings.separator_tokens,
+        &mut settings.non_separator_tokens,
+        &mut settings.dictionary,
+        &mut settings.typo_tolerance.disable_on_words,
+        &mut settings.typo_tolerance.disable_on_attributes,
+    ] {
+        v.sort();
+    }
+
+    for v in settings.synonyms.values_mut() {
+        v.sort();
+    }
+}
+
+#[cfg(feature = "tokio")]
+fn succeeded(task: Task) -> Result<Task> {
+    match task.status {
+        TaskStatus::Succeeded => Ok(task),
+        _ => Err(Error::TaskFailed(Box::new(task))),
+    }
 }
 
 pub trait HasIndexExt {
@@ -145,3 +440,83 @@ where
         c.add_or_update_documents(self).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct Movie;
+
+    impl HasIndex for Movie {
+        const INDEX_UID: &'static str = "movies";
+        const PRIMARY_KEY: &'static str = "id";
+
+        const SORTABLE_ATTRIBUTES: &'static [&'static str] = &["year"];
+        const RANKING_RULES: Option<&'static [&'static str]> = Some(&["words", "year:desc"]);
+        const DISTINCT_ATTRIBUTE: Option<&'static str> = Some("title");
+
+        fn synonyms() -> Option<HashMap<String, Vec<String>>> {
+            Some(HashMap::from([(
+                String::from("film"),
+                vec![String::from("movie")],
+            )]))
+        }
+    }
+
+    #[test]
+    fn declared_settings_override_managed_fields_only() {
+        let remote = IndexSettings {
+            stop_words: vec![String::from("the")],
+            ranking_rules: vec![RankingRule::Typo],
+            ..Default::default()
+        };
+
+        let local = declared_settings::<Movie>(remote);
+
+        assert_eq!(local.stop_words, ["the"]);
+        assert_eq!(
+            local.ranking_rules,
+            [RankingRule::Words, RankingRule::desc("year")]
+        );
+        assert_eq!(local.sortable_attributes, ["year"]);
+        assert_eq!(local.distinct_attribute.as_deref(), Some("title"));
+        assert_eq!(local.synonyms["film"], ["movie"]);
+    }
+
+    #[test]
+    fn searchable_priority_is_kept() {
+        let remote = IndexSettings {
+            searchable_attributes: vec![String::from("overview"), String::from("title")],
+            ..Default::default()
+        };
+        let mut local = IndexSettings {
+            searchable_attributes: vec![String::from("title"), String::from("overview")],
+            ..remote.clone()
+        };
+        normalize(&mut local);
+
+        assert_eq!(local.searchable_attributes, ["title", "overview"]);
+        assert_eq!(
+            SettingsPatch::diff(&remote, &local).searchable_attributes,
+            Some(local.searchable_attributes)
+        );
+    }
+
+    #[test]
+    fn undeclared_live_settings_are_copied() {
+        let live = r#"{"rankingRules":["typo","words"],"searchCutoffMs":150,
+ "localizedAttributes":[{"attributePatterns":["*_sv"],"locales":["swe"]}],
+ "embedders":{"default":{"source":"userProvided","dimensions":3}},
+ "futureSetting":true}"#;
+
+        let live = serde_json::from_str::<IndexSettings>(live).expect("deser");
+        let body = serde_json::to_value(&live).expect("ser");
+
+        assert_eq!(body["rankingRules"], serde_json::json!(["typo", "words"]));
+        assert_eq!(body["searchCutoffMs"], 150);
+        assert_eq!(body["localizedAttributes"][0]["locales"][0], "swe");
+        assert_eq!(body["embedders"]["default"]["dimensions"], 3);
+        assert_eq!(body["futureSetting"], true);
+    }
+}
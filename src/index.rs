@@ -1,41 +1,193 @@
 use std::collections::HashMap;
 
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+
+use crate::{Client, DateTime, RankingRule};
+
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Index {
     pub uid: String,
-    pub created_at: String,
-    pub updated_at: String,
-    pub primary_key: String,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    /// None until set explicitly or inferred from the first documents
+    pub primary_key: Option<String>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+crate::datetime::timestamp_accessors!(Index {
+    created_at => created_at_chrono, created_at_time;
+    updated_at => updated_at_chrono, updated_at_time;
+});
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexesPage {
+    pub results: Vec<Index>,
+    pub offset: u32,
+    pub limit: u32,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct IndexStats {
+    pub number_of_documents: u64,
+    pub is_indexing: bool,
+    /// Number of documents each field appears in
+    pub field_distribution: HashMap<String, u64>,
+    /// Size of the stored documents in bytes
+    #[serde(default)]
+    pub raw_document_db_size: Option<u64>,
+    /// Average size of a stored document in bytes
+    #[serde(default)]
+    pub avg_document_size: Option<u64>,
+    #[serde(default)]
+    pub number_of_embeddings: Option<u64>,
+    #[serde(default)]
+    pub number_of_embedded_documents: Option<u64>,
+}
+
+const STREAM_PAGE_SIZE: u32 = 100;
+
+pub(crate) fn stream(client: Client) -> impl Stream<Item = crate::Result<Index>> {
+    let pages = IndexPages {
+        client,
+        offset: 0,
+        done: false,
+    };
+
+    stream::try_unfold(pages, IndexPages::next)
+        .map_ok(|indexes| stream::iter(indexes).map(Ok))
+        .try_flatten()
+}
+
+struct IndexPages {
+    client: Client,
+    offset: u32,
+    done: bool,
+}
+
+impl IndexPages {
+    async fn next(mut self) -> crate::Result<Option<(Vec<Index>, Self)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page = self
+            .client
+            .list_indexes(self.offset, STREAM_PAGE_SIZE)
+            .await?;
+
+        self.offset = page.offset + page.results.len() as u32;
+        self.done = page.results.is_empty() || page.total <= u64::from(self.offset);
+
+        Ok(Some((page.results, self)))
+    }
+}
+
+/// Settings missing in the response, e.g from older versions of
+/// meilisearch, are defaulted. Settings newer than the oldest supported
+/// version are None when missing and are not sent back.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct IndexSettings {
     pub displayed_attributes: Vec<String>,
     pub searchable_attributes: Vec<String>,
     pub filterable_attributes: Vec<String>,
     pub sortable_attributes: Vec<String>,
-    pub ranking_rules: Vec<String>,
+    pub ranking_rules: Vec<RankingRule>,
     pub stop_words: Vec<String>,
     pub non_separator_tokens: Vec<String>,
     pub separator_tokens: Vec<String>,
     pub dictionary: Vec<String>,
-    pub synonyms: HashMap<String, String>,
-    pub distinct_attribute: serde_json::Value,
+    pub synonyms: HashMap<String, Vec<String>>,
+    pub distinct_attribute: Option<String>,
     pub typo_tolerance: TypoTolerance,
     pub faceting: Faceting,
     pub pagination: Pagination,
     pub proximity_precision: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedders: Option<HashMap<String, Embedder>>,
+    /// `Some(None)` when locales are detected automatically
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::settings::nullable"
+    )]
+    pub localized_attributes: Option<Option<Vec<LocalizedAttribute>>>,
+    /// `Some(None)` for the default of 1500ms
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::settings::nullable"
+    )]
+    pub search_cutoff_ms: Option<Option<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_search: Option<bool>,
+    /// `indexingTime` or `disabled`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix_search: Option<String>,
+
+    /// Settings not known to this version of the crate, kept
+    /// so they are sent back as they were received
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct Embedder {
+    /// `openAi`, `huggingFace`, `ollama`, `rest`, `userProvided` or `composite`
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Only returned redacted by meilisearch, so it is never read
+    /// back and only sent when set
+    #[serde(
+        default,
+        deserialize_with = "ignore_redacted",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_template_max_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary_quantized: Option<bool>,
+
+    /// Source specific options, such as `request` and `response` for `rest`
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Drops values which meilisearch only returns redacted
+fn ignore_redacted<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    <serde::de::IgnoredAny as serde::Deserialize>::deserialize(deserializer).map(|_| None)
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedAttribute {
+    pub attribute_patterns: Vec<String>,
+    /// ISO 639 codes, such as `eng` or `jpn`
+    pub locales: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct TypoTolerance {
     pub enabled: bool,
     pub min_word_size_for_typos: MinWordSizeForTypos,
     pub disable_on_words: Vec<String>,
     pub disable_on_attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_numbers: Option<bool>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -46,9 +198,12 @@ pub struct MinWordSizeForTypos {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Faceting {
     pub max_values_per_facet: i64,
+    /// `alpha` or `count` by facet name, `*` for all facets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_facet_values_by: Option<HashMap<String, String>>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -56,3 +211,74 @@ pub struct Faceting {
 pub struct Pagination {
     pub max_total_hits: i64,
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn deserialize_index_without_primary_key() {
+        let s = r#"{"uid":"users","createdAt":"2024-01-01T00:00:00Z","updatedAt":"2024-01-01T00:00:00Z","primaryKey":null}"#;
+
+        let index = serde_json::from_str::<Index>(s).expect("deser");
+
+        assert_eq!(index.primary_key, None);
+    }
+
+    #[test]
+    fn deserialize_index_stats() {
+        let s =
+            r#"{"numberOfDocuments":2,"isIndexing":false,"fieldDistribution":{"id":2,"name":1}}"#;
+
+        let stats = serde_json::from_str::<IndexStats>(s).expect("deser");
+
+        assert_eq!(stats.number_of_documents, 2);
+        assert_eq!(stats.field_distribution.get("name"), Some(&1));
+        assert_eq!(stats.raw_document_db_size, None);
+    }
+
+    #[test]
+    fn settings_round_trip_keeps_unknown_fields() {
+        let s = r#"{"displayedAttributes":["*"],"synonyms":{"film":["movie","picture"]},
+ "typoTolerance":{"enabled":true,"minWordSizeForTypos":{"oneTypo":5,"twoTypos":9},
+ "disableOnWords":[],"disableOnAttributes":[],"disableOnNumbers":true},
+ "faceting":{"maxValuesPerFacet":100,"sortFacetValuesBy":{"*":"alpha"}},
+ "embedders":{"default":{"source":"userProvided","dimensions":3,"distribution":{"mean":0.7,"sigma":0.3}}},
+ "searchCutoffMs":null,"brandNewSetting":{"on":true}}"#;
+
+        let settings = serde_json::from_str::<IndexSettings>(s).expect("deser");
+
+        assert_eq!(settings.synonyms["film"], ["movie", "picture"]);
+        assert_eq!(settings.typo_tolerance.disable_on_numbers, Some(true));
+        let embedders = settings.embedders.as_ref().expect("embedders");
+        assert_eq!(embedders["default"].dimensions, Some(3));
+        assert!(embedders["default"].extra.contains_key("distribution"));
+        assert_eq!(settings.search_cutoff_ms, Some(None));
+
+        let value = serde_json::to_value(&settings).expect("ser");
+        assert_eq!(value["brandNewSetting"]["on"], true);
+        assert_eq!(value["embedders"]["default"]["distribution"]["mean"], 0.7);
+        assert_eq!(value["searchCutoffMs"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn missing_newer_settings_are_not_sent() {
+        let s = r#"{"displayedAttributes":["*"],"typoTolerance":{"enabled":true},
+ "embedders":{"default":{"source":"openAi","apiKey":"sk-Xxxx...Xxxx"}}}"#;
+
+        let settings = serde_json::from_str::<IndexSettings>(s).expect("deser");
+        let value = serde_json::to_value(&settings).expect("ser");
+
+        for setting in [
+            "facetSearch",
+            "prefixSearch",
+            "localizedAttributes",
+            "searchCutoffMs",
+        ] {
+            assert!(value.get(setting).is_none(), "{setting}");
+        }
+        assert!(value["typoTolerance"].get("disableOnNumbers").is_none());
+        assert!(value["embedders"]["default"].get("apiKey").is_none());
+    }
+}
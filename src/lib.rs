@@ -1,22 +1,55 @@
+mod batch;
+mod bulk;
 mod client;
+mod datetime;
+mod documents;
+mod error;
+mod filter;
 mod has_index;
 mod index;
+mod multi_search;
+mod retry;
 mod search;
+mod settings;
+mod sort;
 mod task;
+mod task_query;
+mod wait;
 
 #[cfg(feature = "hooks")]
 mod task_manager;
 
+#[cfg(feature = "hooks")]
+mod webhook;
+
 #[cfg(feature = "hooks")]
 pub use task_manager::TaskManager;
 
-pub use client::Client;
+#[cfg(feature = "hooks")]
+pub use webhook::{WebhookError, WebhookHandler};
+
+pub use batch::{Batch, BatchStats, BatchesPage, ProcessingProgress, ProgressStep};
+pub use bulk::{BatchProgress, BulkIndexer};
+pub use client::{BuildError, Client, ClientBuilder};
+pub use documents::*;
+pub use error::*;
+pub use filter::*;
 pub use has_index::*;
 pub use index::*;
+pub use multi_search::*;
+pub use retry::RetryPolicy;
 pub use search::*;
+pub use settings::{
+    setting, FacetingPatch, MinWordSizeForTyposPatch, PaginationPatch, Setting, SettingsPatch,
+    TypoTolerancePatch,
+};
+pub use sort::{RankingRule, SortBy};
 
 pub use task::*;
+pub use task_query::{TaskQuery, TasksPage};
+pub use wait::{TasksReport, WaitOptions};
 
+#[cfg(feature = "hooks")]
 use crate::task_manager::TaskPromiseError;
 
 pub mod prelude {
@@ -30,17 +63,108 @@ pub enum Error {
     #[error("transport: {0}")]
     Transport(#[from] reqwest::Error),
 
+    #[error("error response from meili: {status:03}. {error}")]
+    Api { status: u16, error: ApiError },
+
+    /// Unsuccessful response whose body could not be parsed as an [`ApiError`]
     #[error("nok response from meili: {code:03}. Body:{body:?}")]
     UnexpectedNok { code: u16, body: Option<String> },
 
+    #[error("serializing documents: {0}")]
+    Serialize(serde_json::Error),
+
+    #[error("task {} did not succeed: {:?}", .0.uid, .0.status)]
+    TaskFailed(Box<Task>),
+
     #[error("deserializing response: {err}. Body: \n{body}")]
     Deserialize {
         err: serde_json::Error,
         body: String,
     },
 
+    #[error("attribute `{attribute}` is not filterable in index `{index_uid}`")]
+    UnfilterableAttribute {
+        index_uid: &'static str,
+        attribute: String,
+    },
+
+    #[error("attribute `{attribute}` is not sortable in index `{index_uid}`")]
+    UnsortableAttribute {
+        index_uid: &'static str,
+        attribute: String,
+    },
+
+    #[error("task {task_uid} had not stopped after {elapsed:?}, last status: {last_status:?}")]
+    WaitTimeout {
+        task_uid: u64,
+        elapsed: std::time::Duration,
+        last_status: Option<TaskStatus>,
+    },
+
+    #[error("tasks {pending:?} had not stopped after {elapsed:?}")]
+    WaitForTasksTimeout {
+        pending: Vec<u64>,
+        elapsed: std::time::Duration,
+        /// Tasks which had stopped before the timeout
+        report: Box<TasksReport>,
+    },
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    #[error("invalid timestamp `{value}`: {reason}")]
+    InvalidTimestamp { value: String, reason: String },
+
+    #[cfg(feature = "hooks")]
     #[error("waiting for task hook: {0}")]
     TaskPromise(#[from] TaskPromiseError),
 }
 
+impl Error {
+    fn status(&self) -> Option<u16> {
+        match self {
+            Self::Api { status, .. } => Some(*status),
+            Self::UnexpectedNok { code, .. } => Some(*code),
+            Self::Transport(err) => err.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::Api { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> Option<&ErrorCode> {
+        self.api_error().map(|err| &err.code)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        match self.code() {
+            Some(code) => code.is_not_found(),
+            None => self.status() == Some(404),
+        }
+    }
+
+    /// Transient errors, such as timeouts, unavailable upstreams
+    /// or rate limiting, which may succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        if let Self::Transport(err) = self {
+            if err.is_timeout() || err.is_connect() {
+                return true;
+            }
+        }
+
+        self.code().is_some_and(ErrorCode::is_retryable)
+            || matches!(self.status(), Some(429 | 502 | 503 | 504))
+    }
+
+    pub fn is_auth_error(&self) -> bool {
+        match self.api_error() {
+            Some(err) => err.error_type == ErrorType::Auth || err.code.is_auth_error(),
+            None => matches!(self.status(), Some(401 | 403)),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,315 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::{
+    search::{FacetStats, Search, SearchResponse},
+    HasIndex,
+};
+
+/// A single query of a multi search. Hits are deserialized into `T`.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery<T> {
+    index_uid: String,
+
+    #[serde(flatten)]
+    search: Search,
+
+    #[serde(skip)]
+    _hit: PhantomData<fn() -> T>,
+}
+
+impl<T> std::fmt::Debug for SearchQuery<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchQuery")
+            .field("index_uid", &self.index_uid)
+            .field("search", &self.search)
+            .finish()
+    }
+}
+
+impl<T> SearchQuery<T> {
+    pub fn new(index_uid: impl Into<String>, search: impl Into<Search>) -> Self {
+        Self {
+            index_uid: index_uid.into(),
+            search: search.into(),
+            _hit: PhantomData,
+        }
+    }
+
+    /// Creates a query against the index of `T`.
+    pub fn for_index(search: impl Into<Search>) -> Self
+    where
+        T: HasIndex,
+    {
+        Self::new(T::INDEX_UID, search)
+    }
+}
+
+impl<T, S, Q> From<(S, Q)> for SearchQuery<T>
+where
+    S: Into<String>,
+    Q: Into<Search>,
+{
+    fn from((index_uid, search): (S, Q)) -> Self {
+        Self::new(index_uid, search)
+    }
+}
+
+/// A set of queries which can be sent in a single multi search request.
+///
+/// Implemented for `Vec<SearchQuery<T>>` where all queries share the
+/// same hit type, and for tuples of up to eight `SearchQuery`s with
+/// different hit types. The output has the same shape as the input.
+pub trait MultiSearchQueries: serde::Serialize {
+    type Output: serde::de::DeserializeOwned;
+}
+
+impl<T> MultiSearchQueries for Vec<SearchQuery<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Output = Vec<SearchResponse<T>>;
+}
+
+macro_rules! impl_multi_search_queries_for_tuple {
+    ($($t:ident),+) => {
+        impl<$($t),+> MultiSearchQueries for ($(SearchQuery<$t>,)+)
+        where
+            $($t: serde::de::DeserializeOwned,)+
+        {
+            type Output = ($(SearchResponse<$t>,)+);
+        }
+    };
+}
+
+impl_multi_search_queries_for_tuple!(A);
+impl_multi_search_queries_for_tuple!(A, B);
+impl_multi_search_queries_for_tuple!(A, B, C);
+impl_multi_search_queries_for_tuple!(A, B, C, D);
+impl_multi_search_queries_for_tuple!(A, B, C, D, E);
+impl_multi_search_queries_for_tuple!(A, B, C, D, E, F);
+impl_multi_search_queries_for_tuple!(A, B, C, D, E, F, G);
+impl_multi_search_queries_for_tuple!(A, B, C, D, E, F, G, H);
+
+#[derive(serde::Serialize)]
+pub(crate) struct MultiSearchRequest<Q> {
+    pub queries: Q,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct MultiSearchResponse<R> {
+    pub results: R,
+}
+
+/// Searches several indexes and merges the hits into a single ranked list.
+///
+/// Since all hits end up in the same list they are deserialized into the
+/// same type, use an untagged enum to combine documents of different indexes.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearch {
+    federation: Federation,
+    queries: Vec<FederatedQuery>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Federation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FederatedQuery {
+    index_uid: String,
+
+    #[serde(flatten)]
+    search: Search,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    federation_options: Option<FederationOptions>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FederationOptions {
+    weight: f64,
+}
+
+impl FederatedSearch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pagination of `search` is ignored, use [`FederatedSearch::offset`]
+    /// and [`FederatedSearch::limit`] instead.
+    pub fn query(mut self, index_uid: impl Into<String>, search: impl Into<Search>) -> Self {
+        self.queries.push(FederatedQuery {
+            index_uid: index_uid.into(),
+            search: search.into().without_pagination(),
+            federation_options: None,
+        });
+        self
+    }
+
+    /// Adds a query whose ranking scores are multiplied by `weight`
+    /// default 1.0
+    pub fn weighted_query(
+        mut self,
+        index_uid: impl Into<String>,
+        search: impl Into<Search>,
+        weight: f64,
+    ) -> Self {
+        self.queries.push(FederatedQuery {
+            index_uid: index_uid.into(),
+            search: search.into().without_pagination(),
+            federation_options: Some(FederationOptions { weight }),
+        });
+        self
+    }
+
+    /// Number of merged hits to skip
+    pub fn offset(mut self, n: Option<u32>) -> Self {
+        self.federation.offset = n;
+        self
+    }
+
+    /// Maximum number of merged hits returned
+    pub fn limit(mut self, n: Option<u32>) -> Self {
+        self.federation.limit = n;
+        self
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearchResponse<T> {
+    /// Merged results of all queries
+    pub hits: Vec<FederatedHit<T>>,
+
+    pub offset: Option<u32>,
+
+    pub limit: Option<u32>,
+
+    /// Estimated total number of matches
+    pub estimated_total_hits: Option<u32>,
+
+    /// Distribution of the given facets
+    pub facet_distribution: Option<HashMap<String, HashMap<String, u32>>>,
+
+    pub facet_stats: Option<HashMap<String, FacetStats>>,
+
+    /// Processing time of the query
+    pub processing_time_ms: u32,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FederatedHit<T> {
+    #[serde(rename = "_federation")]
+    pub federation: FederationHitInfo,
+
+    #[serde(flatten)]
+    pub result: T,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationHitInfo {
+    /// Index the hit originates from
+    pub index_uid: String,
+
+    /// Position of the query which matched the hit
+    pub queries_position: usize,
+
+    /// Ranking score of the hit multiplied by the query weight
+    pub weighted_ranking_score: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Product {
+        id: u64,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Brand {
+        name: String,
+    }
+
+    #[test]
+    fn serialize_multi_search_request() {
+        let queries = (
+            SearchQuery::<Product>::new("products", "shoe"),
+            SearchQuery::<Brand>::new("brands", Search::new("shoe").page(Some(2))),
+        );
+
+        let json = serde_json::to_value(MultiSearchRequest { queries }).expect("ser");
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "queries": [
+                    { "indexUid": "products", "q": "shoe" },
+                    { "indexUid": "brands", "q": "shoe", "page": 2 },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_heterogeneous_multi_search_response() {
+        let s = r#"
+{"results":[
+  {"indexUid":"products","hits":[{"id":1}],"query":"shoe","processingTimeMs":1,"limit":20,"offset":0,"estimatedTotalHits":1},
+  {"indexUid":"brands","hits":[{"name":"Acme"}],"query":"shoe","processingTimeMs":0,"limit":20,"offset":0,"estimatedTotalHits":1}
+]}"#;
+
+        let res = serde_json::from_str::<
+            MultiSearchResponse<
+                <(SearchQuery<Product>, SearchQuery<Brand>) as MultiSearchQueries>::Output,
+            >,
+        >(s)
+        .expect("deser");
+
+        let (products, brands) = res.results;
+        assert_eq!(products.hits[0].result.id, 1);
+        assert_eq!(brands.hits[0].result.name, "Acme");
+    }
+
+    #[test]
+    fn deserialize_federated_hit() {
+        let s = r#"
+{"hits":[{"id":3,"_federation":{"indexUid":"products","queriesPosition":0,"weightedRankingScore":0.9}}],
+ "processingTimeMs":2,"limit":20,"offset":0,"estimatedTotalHits":1}"#;
+
+        let res = serde_json::from_str::<FederatedSearchResponse<Product>>(s).expect("deser");
+
+        assert_eq!(res.hits[0].result.id, 3);
+        assert_eq!(res.hits[0].federation.index_uid, "products");
+    }
+
+    #[test]
+    fn federated_queries_have_no_pagination() {
+        let search = FederatedSearch::new()
+            .query("products", Search::new("shoe").limit(Some(5)).page(Some(2)))
+            .weighted_query("brands", Search::new("shoe").offset(Some(10)), 0.5)
+            .limit(Some(15));
+
+        let value = serde_json::to_value(&search).expect("ser");
+
+        assert_eq!(value["federation"]["limit"], 15);
+        for query in value["queries"].as_array().expect("queries") {
+            for key in ["offset", "limit", "page", "hitsPerPage"] {
+                assert!(query.get(key).is_none(), "{key} in {query}");
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use crate::Error;
+
+/// Decides if and when failed requests are retried.
+///
+/// Only idempotent requests, and POSTs which only read data such as
+/// searches, are retried unless [`RetryPolicy::retry_non_idempotent`] is set.
+/// Connection errors are always safe to retry since the request never
+/// reached the server.
+///
+/// Without the `tokio` feature there is no timer to back off with, so
+/// requests are attempted once by default and retries enabled with
+/// [`RetryPolicy::max_attempts`] are sent without waiting.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_statuses: Vec<u16>,
+    retry_transport_errors: bool,
+    retry_non_idempotent: bool,
+}
+
+#[cfg(feature = "tokio")]
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+#[cfg(not(feature = "tokio"))]
+const DEFAULT_MAX_ATTEMPTS: u32 = 1;
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: true,
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_transport_errors: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retries, every request is attempted once
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total number of attempts, including the first one
+    /// default 3, 1 without the `tokio` feature
+    pub fn max_attempts(mut self, n: u32) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+
+    /// Delay before the first retry
+    /// default 100ms
+    pub fn initial_backoff(mut self, dur: Duration) -> Self {
+        self.initial_backoff = dur;
+        self
+    }
+
+    /// Upper bound of the delay between attempts
+    /// default 2s
+    pub fn max_backoff(mut self, dur: Duration) -> Self {
+        self.max_backoff = dur;
+        self
+    }
+
+    /// Factor the delay grows by after every attempt
+    /// default 2.0
+    pub fn multiplier(mut self, m: f64) -> Self {
+        self.multiplier = m;
+        self
+    }
+
+    /// Randomize each delay between half and all of its value
+    /// default true
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Response statuses which are retried
+    /// default [429, 502, 503, 504]
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Retry timeouts and connection errors
+    /// default true
+    pub fn retry_transport_errors(mut self, retry: bool) -> Self {
+        self.retry_transport_errors = retry;
+        self
+    }
+
+    /// Also retry requests which may have side effects, such as adding documents.
+    /// This can enqueue the same task twice.
+    /// default false
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    pub(crate) fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn should_retry(&self, err: &Error, idempotent: bool) -> bool {
+        let may_resend = idempotent || self.retry_non_idempotent;
+
+        match err {
+            Error::Transport(err) if self.retry_transport_errors => {
+                err.is_connect() || (may_resend && (err.is_timeout() || err.is_request()))
+            }
+
+            Error::Api { status, .. } | Error::UnexpectedNok { code: status, .. } => {
+                may_resend && self.retry_statuses.contains(status)
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Delay before attempt `attempt + 1`
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .multiplier
+            .powi(i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX));
+        let secs = (self.initial_backoff.as_secs_f64() * exp).min(self.max_backoff.as_secs_f64());
+
+        // negative or NaN for odd multipliers
+        let delay = Duration::try_from_secs_f64(secs).unwrap_or(self.max_backoff);
+
+        if self.jitter {
+            delay.mul_f64(0.5 + random_fraction() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Random number in [0, 1), good enough for jitter
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Without the `tokio` feature there is no timer to wait on,
+/// so this returns immediately, see [`RetryPolicy`].
+pub(crate) async fn sleep(dur: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(dur).await;
+
+    #[cfg(not(feature = "tokio"))]
+    let _ = dur;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "tokio"))]
+    fn no_retries_by_default_without_timer() {
+        assert_eq!(RetryPolicy::default().attempts(), 1);
+    }
+
+    #[test]
+    fn backoff_grows_until_max() {
+        let policy = RetryPolicy::default().jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::default();
+
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(Duration::from_millis(100) <= delay && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn statuses_retried_only_when_idempotent() {
+        let policy = RetryPolicy::default();
+        let err = Error::UnexpectedNok {
+            code: 503,
+            body: None,
+        };
+
+        assert!(policy.should_retry(&err, true));
+        assert!(!policy.should_retry(&err, false));
+        assert!(policy
+            .clone()
+            .retry_non_idempotent(true)
+            .should_retry(&err, false));
+
+        let err = Error::UnexpectedNok {
+            code: 400,
+            body: None,
+        };
+        assert!(!policy.should_retry(&err, true));
+    }
+
+    #[test]
+    fn backoff_with_odd_multiplier_does_not_panic() {
+        let policy = RetryPolicy::default().jitter(false);
+
+        assert_eq!(
+            policy.clone().multiplier(-2.0).backoff(2),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            policy.clone().multiplier(f64::NAN).backoff(2),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            policy.multiplier(f64::MAX).backoff(u32::MAX),
+            Duration::from_secs(2)
+        );
+    }
+}
@@ -1,8 +1,10 @@
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 use std::collections::HashMap;
+use tracing::warn;
 
-use crate::HasIndex;
+use crate::{filter, setting, sort, Client, Filter, HasIndex, SortBy};
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Search {
     #[serde(rename = "q")]
@@ -30,7 +32,7 @@ pub struct Search {
 
     /// Filter queries by an attribute's value
     #[serde(skip_serializing_if = "Option::is_none")]
-    filter: Option<String>,
+    filter: Option<Filter>,
 
     /// Display the count of matches per facet
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -78,10 +80,9 @@ pub struct Search {
     #[serde(skip_serializing_if = "Option::is_none")]
     show_matches_position: Option<bool>,
 
-    // @TODO: create trait that allows calling desc asc on str-types
     /// Sort search results by an attribute's value
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    sort: Vec<String>,
+    sort: Vec<SortBy>,
 
     /// Strategy used to match query terms within documents
     /// default "last"
@@ -139,6 +140,16 @@ impl Search {
         }
     }
 
+    pub fn offset(mut self, n: Option<u32>) -> Self {
+        self.offset = n;
+        self
+    }
+
+    pub fn limit(mut self, n: Option<u32>) -> Self {
+        self.limit = n;
+        self
+    }
+
     pub fn hits_per_page(mut self, n: Option<u32>) -> Self {
         self.hits_per_page = n;
         self
@@ -149,19 +160,49 @@ impl Search {
         self
     }
 
-    pub fn sort_by(mut self, s: impl Into<String>) -> Self {
+    /// Accepts either a [`SortBy`] or a sort string such as `"price:asc"`
+    pub fn sort_by(mut self, s: impl Into<SortBy>) -> Self {
         self.sort.push(s.into());
         self
     }
 
-    pub fn filter<S>(mut self, f: Option<S>) -> Self
+    /// Accepts either a [`Filter`] or a raw filter string
+    pub fn filter<F>(mut self, f: Option<F>) -> Self
     where
-        String: From<S>,
+        F: Into<Filter>,
     {
-        self.filter = f.map(String::from);
+        self.filter = f.map(Into::into);
         self
     }
 
+    /// Removes offset, limit, page and hits per page, which are
+    /// rejected inside federated queries.
+    pub(crate) fn without_pagination(self) -> Self {
+        Self {
+            offset: None,
+            limit: None,
+            hits_per_page: None,
+            page: None,
+            ..self
+        }
+    }
+
+    /// Fails if the filter references attributes which are not
+    /// filterable in the index of `T`.
+    pub(crate) fn check_filterable<T>(&self) -> crate::Result<()>
+    where
+        T: HasIndex,
+    {
+        filter::ensure_filterable::<T>(self.filter.as_ref())
+    }
+
+    pub(crate) fn check_sortable<T>(&self) -> crate::Result<()>
+    where
+        T: HasIndex,
+    {
+        sort::ensure_sortable::<T>(self.sort.iter().filter_map(SortBy::attribute))
+    }
+
     pub async fn search<T>(self, client: &crate::Client) -> crate::Result<SearchResponse<T>>
     where
         T: HasIndex,
@@ -169,6 +210,113 @@ impl Search {
     {
         T::search(client, self).await
     }
+
+    /// Streams all hits of the search, fetching one page at a time.
+    ///
+    /// Pages by `page`/`hits_per_page` if either is set, otherwise by
+    /// `offset`/`limit`. Meilisearch never returns more than the
+    /// `max_total_hits` pagination setting of the index, a warning is
+    /// logged when the hits are truncated by it. API keys which cannot
+    /// read settings rely on the total hits returned with each page.
+    pub fn stream<T>(self, client: &Client) -> impl Stream<Item = crate::Result<T>>
+    where
+        T: HasIndex,
+        T: serde::de::DeserializeOwned,
+    {
+        let pages = SearchPages {
+            client: client.clone(),
+            paged: self.page.is_some() || self.hits_per_page.is_some(),
+            search: self,
+            started: false,
+            max_total_hits: None,
+            done: false,
+        };
+
+        stream::try_unfold(pages, SearchPages::next::<T>)
+            .map_ok(|hits| stream::iter(hits).map(Ok))
+            .try_flatten()
+    }
+}
+
+struct SearchPages {
+    client: Client,
+    search: Search,
+    paged: bool,
+    started: bool,
+    /// None if the pagination settings could not be read
+    max_total_hits: Option<u64>,
+    done: bool,
+}
+
+impl SearchPages {
+    async fn next<T>(mut self) -> crate::Result<Option<(Vec<T>, Self)>>
+    where
+        T: HasIndex,
+        T: serde::de::DeserializeOwned,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        if !self.started {
+            self.search.check_filterable::<T>()?;
+            self.search.check_sortable::<T>()?;
+
+            // search-only keys may not read settings
+            self.max_total_hits = match self
+                .client
+                .get_setting::<setting::Pagination>(T::INDEX_UID)
+                .await
+            {
+                Ok(pagination) => Some(pagination.max_total_hits.max(0) as u64),
+                Err(err) => {
+                    warn!(
+                        "meili: cannot read pagination settings of `{}`, relying on total hits: {err}",
+                        T::INDEX_UID
+                    );
+                    None
+                }
+            };
+            self.started = true;
+        }
+
+        let res = self
+            .client
+            .search::<T>(T::INDEX_UID, self.search.clone())
+            .await?;
+
+        let n_hits = res.hits.len() as u64;
+        let total_hits = res.total_hits.or(res.estimated_total_hits).map(u64::from);
+
+        if self.paged {
+            let page = res.page.unwrap_or(1);
+            self.done = n_hits == 0 || res.total_pages.unwrap_or(0) <= page;
+            self.search.page = Some(page + 1);
+        } else {
+            let limit = self.search.limit.unwrap_or(20);
+            let offset = u64::from(res.offset.unwrap_or(0)) + n_hits;
+            self.done = limit == 0
+                || n_hits < u64::from(limit)
+                || total_hits.is_some_and(|total| total <= offset)
+                || self.max_total_hits.is_some_and(|max| max <= offset);
+            self.search.offset = Some(offset as u32);
+            self.search.limit = Some(limit);
+        }
+
+        if let Some(max_total_hits) = self
+            .max_total_hits
+            .filter(|max| self.done && 0 < *max && Some(*max) <= total_hits)
+        {
+            warn!(
+                "meili: search in `{}` truncated at max_total_hits ({max_total_hits})",
+                T::INDEX_UID
+            );
+        }
+
+        let hits = res.hits.into_iter().map(|hit| hit.result).collect();
+
+        Ok(Some((hits, self)))
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use reqwest::Method;
+
+use crate::{
+    Embedder, Faceting, IndexSettings, LocalizedAttribute, MinWordSizeForTypos, Pagination,
+    RankingRule, TypoTolerance,
+};
+
+/// Partial index settings, only the fields which are set are
+/// sent to meilisearch and the others are left as they are.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displayed_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub searchable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filterable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sortable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking_rules: Option<Vec<RankingRule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_words: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_separator_tokens: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator_tokens: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<HashMap<String, Vec<String>>>,
+    /// `Some(None)` removes the distinct attribute
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "nullable")]
+    pub distinct_attribute: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typo_tolerance: Option<TypoTolerancePatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faceting: Option<FacetingPatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<PaginationPatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proximity_precision: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedders: Option<HashMap<String, Embedder>>,
+    /// `Some(None)` detects locales automatically
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "nullable")]
+    pub localized_attributes: Option<Option<Vec<LocalizedAttribute>>>,
+    /// `Some(None)` resets to the default of 1500ms
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "nullable")]
+    pub search_cutoff_ms: Option<Option<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_search: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix_search: Option<String>,
+
+    /// Settings not known to this version of the crate
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Keeps explicit nulls apart from missing fields
+pub(crate) mod nullable {
+    use serde::{Deserialize, Serialize};
+
+    pub fn serialize<S, T>(v: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: Serialize,
+    {
+        v.as_ref().and_then(Option::as_ref).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
+
+/// Partial [`TypoTolerance`], for updates which leave unset fields as they are.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypoTolerancePatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_word_size_for_typos: Option<MinWordSizeForTyposPatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_words: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_numbers: Option<bool>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinWordSizeForTyposPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_typo: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_typos: Option<i64>,
+}
+
+/// Partial [`Faceting`], for updates which leave unset fields as they are.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetingPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values_per_facet: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_facet_values_by: Option<HashMap<String, String>>,
+}
+
+/// Partial [`Pagination`], for updates which leave unset fields as they are.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_hits: Option<i64>,
+}
+
+/// Changed value of a field, nested settings are diffed by their own patch
+macro_rules! diff_field {
+    ($from:ident, $to:ident, $field:ident) => {
+        ($from.$field != $to.$field).then(|| $to.$field.clone())
+    };
+    ($from:ident, $to:ident, $field:ident, $nested:ident) => {
+        ($from.$field != $to.$field).then(|| $nested::diff(&$from.$field, &$to.$field))
+    };
+}
+
+/// Generates `diff` and `From` for the patch of a settings struct. Optional
+/// settings are None when not supported by the server and never sent then.
+macro_rules! patch_fields {
+    (
+        $patch:ident from $full:ident {
+            $($field:ident $(as $nested:ident)?,)+
+        }
+        $(optional { $($optional:ident,)+ })?
+        $(keep $extra:ident)?
+    ) => {
+        impl $patch {
+            /// Fields of `to` which differ from `from`
+            pub(crate) fn diff(from: &$full, to: &$full) -> Self {
+                Self {
+                    $($field: diff_field!(from, to, $field $(, $nested)?),)+
+                    $($($optional: diff_field!(from, to, $optional).flatten(),)+)?
+                    $($extra: to
+                        .$extra
+                        .iter()
+                        .filter(|(k, v)| from.$extra.get(*k) != Some(*v))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),)?
+                }
+            }
+        }
+
+        impl From<$full> for $patch {
+            fn from(settings: $full) -> Self {
+                Self {
+                    $($field: Some(settings.$field.into()),)+
+                    $($($optional: settings.$optional,)+)?
+                    $($extra: settings.$extra,)?
+                }
+            }
+        }
+    };
+}
+
+patch_fields! {
+    TypoTolerancePatch from TypoTolerance {
+        enabled,
+        min_word_size_for_typos as MinWordSizeForTyposPatch,
+        disable_on_words,
+        disable_on_attributes,
+    }
+    optional {
+        disable_on_numbers,
+    }
+}
+
+patch_fields! {
+    MinWordSizeForTyposPatch from MinWordSizeForTypos {
+        one_typo,
+        two_typos,
+    }
+}
+
+patch_fields! {
+    FacetingPatch from Faceting {
+        max_values_per_facet,
+    }
+    optional {
+        sort_facet_values_by,
+    }
+}
+
+patch_fields! {
+    PaginationPatch from Pagination {
+        max_total_hits,
+    }
+}
+
+patch_fields! {
+    SettingsPatch from IndexSettings {
+        displayed_attributes,
+        searchable_attributes,
+        filterable_attributes,
+        sortable_attributes,
+        ranking_rules,
+        stop_words,
+        non_separator_tokens,
+        separator_tokens,
+        dictionary,
+        synonyms,
+        distinct_attribute,
+        typo_tolerance as TypoTolerancePatch,
+        faceting as FacetingPatch,
+        pagination as PaginationPatch,
+        proximity_precision,
+    }
+    optional {
+        embedders,
+        localized_attributes,
+        search_cutoff_ms,
+        facet_search,
+        prefix_search,
+    }
+    keep extra
+}
+
+impl SettingsPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A single index setting with its own route, `/indexes/{uid}/settings/{PATH}`.
+///
+/// Marker types for each setting are found in [`setting`].
+pub trait Setting {
+    const PATH: &'static str;
+
+    /// PATCH for settings merged with the current value, PUT for those replaced
+    const UPDATE_METHOD: Method;
+
+    type Value: serde::Serialize + serde::de::DeserializeOwned;
+
+    /// Body of updates, a patch for settings updated with PATCH
+    type Update: serde::Serialize;
+}
+
+/// The update type if given, otherwise the value type
+macro_rules! update_type {
+    ($update:ty, $value:ty) => {
+        $update
+    };
+    ($value:ty) => {
+        $value
+    };
+}
+
+macro_rules! settings {
+    ($($name:ident => $path:literal, $method:ident, $value:ty $(, $update:ty)?;)+) => {
+        $(
+            #[derive(Debug, Clone, Copy)]
+            pub struct $name;
+
+            impl super::Setting for $name {
+                const PATH: &'static str = $path;
+                const UPDATE_METHOD: reqwest::Method = reqwest::Method::$method;
+                type Value = $value;
+                type Update = update_type!($($update,)? $value);
+            }
+        )+
+    };
+}
+
+/// Marker types for the per-setting routes, used with
+/// [`Client::get_setting`](crate::Client::get_setting) and friends.
+pub mod setting {
+    use std::collections::HashMap;
+
+    settings! {
+        DisplayedAttributes => "displayed-attributes", PUT, Vec<String>;
+        SearchableAttributes => "searchable-attributes", PUT, Vec<String>;
+        FilterableAttributes => "filterable-attributes", PUT, Vec<String>;
+        SortableAttributes => "sortable-attributes", PUT, Vec<String>;
+        RankingRules => "ranking-rules", PUT, Vec<crate::RankingRule>;
+        StopWords => "stop-words", PUT, Vec<String>;
+        NonSeparatorTokens => "non-separator-tokens", PUT, Vec<String>;
+        SeparatorTokens => "separator-tokens", PUT, Vec<String>;
+        Dictionary => "dictionary", PUT, Vec<String>;
+        Synonyms => "synonyms", PUT, HashMap<String, Vec<String>>;
+        DistinctAttribute => "distinct-attribute", PUT, Option<String>;
+        TypoTolerance => "typo-tolerance", PATCH, crate::TypoTolerance, super::TypoTolerancePatch;
+        Faceting => "faceting", PATCH, crate::Faceting, super::FacetingPatch;
+        Pagination => "pagination", PATCH, crate::Pagination, super::PaginationPatch;
+        ProximityPrecision => "proximity-precision", PUT, String;
+        Embedders => "embedders", PATCH, HashMap<String, crate::Embedder>;
+        LocalizedAttributes => "localized-attributes", PUT, Option<Vec<crate::LocalizedAttribute>>;
+        SearchCutoffMs => "search-cutoff-ms", PUT, Option<u64>;
+        FacetSearch => "facet-search", PUT, bool;
+        PrefixSearch => "prefix-search", PUT, String;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn diff_only_contains_changes() {
+        let from = IndexSettings {
+            pagination: Pagination {
+                max_total_hits: 1000,
+            },
+            ..Default::default()
+        };
+
+        let to = IndexSettings {
+            stop_words: vec![String::from("the")],
+            ..from.clone()
+        };
+
+        let patch = SettingsPatch::diff(&from, &to);
+
+        assert_eq!(
+            serde_json::to_value(&patch).expect("ser"),
+            serde_json::json!({"stopWords": ["the"]})
+        );
+        assert!(SettingsPatch::diff(&from, &from).is_empty());
+    }
+
+    #[test]
+    fn nested_diff_only_contains_changes() {
+        let from = IndexSettings::default();
+        let mut to = from.clone();
+        to.typo_tolerance.min_word_size_for_typos.two_typos = 9;
+
+        assert_eq!(
+            serde_json::to_value(SettingsPatch::diff(&from, &to)).expect("ser"),
+            serde_json::json!({"typoTolerance": {"minWordSizeForTypos": {"twoTypos": 9}}})
+        );
+    }
+
+    #[test]
+    fn deserialize_partial_settings() {
+        let patch =
+            serde_json::from_str::<SettingsPatch>(r#"{"filterableAttributes":["tenant_id"]}"#)
+                .expect("deser");
+
+        assert_eq!(
+            patch.filterable_attributes,
+            Some(vec![String::from("tenant_id")])
+        );
+        assert_eq!(patch.pagination, None);
+    }
+
+    #[test]
+    fn explicit_null_resets_setting() {
+        let patch = SettingsPatch {
+            search_cutoff_ms: Some(None),
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(&patch).expect("ser");
+        assert_eq!(value, serde_json::json!({"searchCutoffMs": null}));
+
+        assert_eq!(
+            serde_json::from_value::<SettingsPatch>(value).expect("deser"),
+            patch
+        );
+    }
+
+    #[test]
+    fn removed_distinct_attribute_is_null() {
+        let from = IndexSettings {
+            distinct_attribute: Some(String::from("title")),
+            ..Default::default()
+        };
+        let to = IndexSettings::default();
+
+        assert_eq!(
+            serde_json::to_value(SettingsPatch::diff(&from, &to)).expect("ser"),
+            serde_json::json!({"distinctAttribute": null})
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt;
+
+use crate::{filter, Error, HasIndex};
+
+/// Ranking rule of an index, in settings given in order of importance.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RankingRule {
+    Words,
+    Typo,
+    Proximity,
+    Attribute,
+    Sort,
+    Exactness,
+    /// Custom rule, ascending values of an attribute first
+    Asc(String),
+    /// Custom rule, descending values of an attribute first
+    Desc(String),
+
+    /// Rule not known to this version of the crate
+    Unknown(String),
+}
+
+impl RankingRule {
+    pub fn asc(attribute: impl Into<String>) -> Self {
+        Self::Asc(attribute.into())
+    }
+
+    pub fn desc(attribute: impl Into<String>) -> Self {
+        Self::Desc(attribute.into())
+    }
+
+    /// Attribute of custom rules
+    pub fn attribute(&self) -> Option<&str> {
+        match self {
+            Self::Asc(attribute) | Self::Desc(attribute) => Some(attribute),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for RankingRule {
+    fn from(s: &str) -> Self {
+        match s {
+            "words" => Self::Words,
+            "typo" => Self::Typo,
+            "proximity" => Self::Proximity,
+            "attribute" => Self::Attribute,
+            "sort" => Self::Sort,
+            "exactness" => Self::Exactness,
+            _ => match s.rsplit_once(':') {
+                Some((attribute, "asc")) => Self::Asc(String::from(attribute)),
+                Some((attribute, "desc")) => Self::Desc(String::from(attribute)),
+                _ => Self::Unknown(String::from(s)),
+            },
+        }
+    }
+}
+
+impl From<String> for RankingRule {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl fmt::Display for RankingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Words => f.write_str("words"),
+            Self::Typo => f.write_str("typo"),
+            Self::Proximity => f.write_str("proximity"),
+            Self::Attribute => f.write_str("attribute"),
+            Self::Sort => f.write_str("sort"),
+            Self::Exactness => f.write_str("exactness"),
+            Self::Asc(attribute) => write!(f, "{attribute}:asc"),
+            Self::Desc(attribute) => write!(f, "{attribute}:desc"),
+            Self::Unknown(s) => f.write_str(s),
+        }
+    }
+}
+
+impl serde::Serialize for RankingRule {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RankingRule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Sort expression of a search, strings such as `"price:asc"` are
+/// converted with `From`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortBy {
+    Asc(String),
+    Desc(String),
+    /// Distance from a point, requires `_geo` to be sortable
+    GeoPoint {
+        lat: f64,
+        lng: f64,
+        desc: bool,
+    },
+
+    /// Expression not understood by this crate, sent as is
+    Raw(String),
+}
+
+impl SortBy {
+    pub fn asc(attribute: impl Into<String>) -> Self {
+        Self::Asc(attribute.into())
+    }
+
+    pub fn desc(attribute: impl Into<String>) -> Self {
+        Self::Desc(attribute.into())
+    }
+
+    /// Nearest documents first
+    pub fn geo_point(lat: f64, lng: f64) -> Self {
+        Self::GeoPoint {
+            lat,
+            lng,
+            desc: false,
+        }
+    }
+
+    /// Farthest documents first
+    pub fn geo_point_desc(lat: f64, lng: f64) -> Self {
+        Self::GeoPoint {
+            lat,
+            lng,
+            desc: true,
+        }
+    }
+
+    /// Attribute which must be sortable, None for raw expressions
+    pub fn attribute(&self) -> Option<&str> {
+        match self {
+            Self::Asc(attribute) | Self::Desc(attribute) => Some(attribute),
+            Self::GeoPoint { .. } => Some("_geo"),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+impl From<&str> for SortBy {
+    fn from(s: &str) -> Self {
+        let Some((expr, direction)) = s.rsplit_once(':') else {
+            return Self::Raw(String::from(s));
+        };
+
+        let desc = match direction {
+            "asc" => false,
+            "desc" => true,
+            _ => return Self::Raw(String::from(s)),
+        };
+
+        if let Some(point) = expr
+            .strip_prefix("_geoPoint(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return match point
+                .split_once(',')
+                .map(|(lat, lng)| (lat.trim().parse(), lng.trim().parse()))
+            {
+                Some((Ok(lat), Ok(lng))) => Self::GeoPoint { lat, lng, desc },
+                _ => Self::Raw(String::from(s)),
+            };
+        }
+
+        match desc {
+            false => Self::Asc(String::from(expr)),
+            true => Self::Desc(String::from(expr)),
+        }
+    }
+}
+
+impl From<String> for SortBy {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<&String> for SortBy {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+/// Custom ranking rules sort by their attribute, others are kept raw
+impl From<RankingRule> for SortBy {
+    fn from(rule: RankingRule) -> Self {
+        match rule {
+            RankingRule::Asc(attribute) => Self::Asc(attribute),
+            RankingRule::Desc(attribute) => Self::Desc(attribute),
+            rule => Self::Raw(rule.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Asc(attribute) => write!(f, "{attribute}:asc"),
+            Self::Desc(attribute) => write!(f, "{attribute}:desc"),
+            Self::GeoPoint { lat, lng, desc } => write!(
+                f,
+                "_geoPoint({lat}, {lng}):{}",
+                if *desc { "desc" } else { "asc" }
+            ),
+            Self::Raw(s) => f.write_str(s),
+        }
+    }
+}
+
+impl serde::Serialize for SortBy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Fails with [`Error::UnsortableAttribute`] if any of `attributes`
+/// is not sortable in the index of `T`.
+pub(crate) fn ensure_sortable<'a, T>(
+    attributes: impl IntoIterator<Item = &'a str>,
+) -> crate::Result<()>
+where
+    T: HasIndex,
+{
+    match attributes
+        .into_iter()
+        .find(|attr| !filter::is_declared(T::SORTABLE_ATTRIBUTES, attr))
+    {
+        Some(attribute) => Err(Error::UnsortableAttribute {
+            index_uid: T::INDEX_UID,
+            attribute: String::from(attribute),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn ranking_rules_round_trip() {
+        let rules = [
+            "words",
+            "typo",
+            "release_date:desc",
+            "rank:asc",
+            "wordPosition",
+        ];
+
+        let parsed = rules.map(RankingRule::from);
+
+        assert_eq!(parsed[2], RankingRule::desc("release_date"));
+        assert_eq!(parsed[3], RankingRule::asc("rank"));
+        assert_eq!(
+            parsed[4],
+            RankingRule::Unknown(String::from("wordPosition"))
+        );
+        assert_eq!(parsed.map(|r| r.to_string()), rules);
+    }
+
+    #[test]
+    fn sort_by_parses_and_formats() {
+        assert_eq!(SortBy::from("price:asc"), SortBy::asc("price"));
+        assert_eq!(SortBy::from("sites.id:desc"), SortBy::desc("sites.id"));
+        assert_eq!(
+            SortBy::from("_geoPoint(48.85, 2.35):asc"),
+            SortBy::geo_point(48.85, 2.35)
+        );
+        assert_eq!(SortBy::from("price"), SortBy::Raw(String::from("price")));
+
+        assert_eq!(
+            SortBy::geo_point_desc(48.85, -2.5).to_string(),
+            "_geoPoint(48.85, -2.5):desc"
+        );
+        assert_eq!(SortBy::geo_point(0.0, 0.0).attribute(), Some("_geo"));
+    }
+
+    #[test]
+    fn sort_by_from_ranking_rule() {
+        assert_eq!(
+            SortBy::from(RankingRule::desc("release_date")),
+            SortBy::desc("release_date")
+        );
+        assert_eq!(
+            SortBy::from(RankingRule::Words),
+            SortBy::Raw(String::from("words"))
+        );
+    }
+
+    struct Product;
+
+    impl HasIndex for Product {
+        const INDEX_UID: &'static str = "products";
+        const PRIMARY_KEY: &'static str = "id";
+
+        const SORTABLE_ATTRIBUTES: &'static [&'static str] = &["price", "sites"];
+    }
+
+    #[test]
+    fn unsortable_attribute_is_rejected() {
+        assert!(ensure_sortable::<Product>(["price", "sites.id"]).is_ok());
+
+        assert!(matches!(
+            ensure_sortable::<Product>(["price", "_geo"]),
+            Err(Error::UnsortableAttribute { attribute, .. }) if attribute == "_geo"
+        ));
+    }
+}
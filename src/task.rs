@@ -1,4 +1,6 @@
-use crate::{DateTime, IndexSettings};
+use std::collections::HashMap;
+
+use crate::{datetime, DateTime, ErrorCode, ErrorType, SettingsPatch};
 
 pub trait AsTaskUid {
     fn as_task_uid(&self) -> u64;
@@ -27,7 +29,8 @@ impl AsTaskUid for &'_ Task {
 pub struct TaskRef {
     #[serde(rename = "taskUid")]
     pub uid: u64,
-    pub index_uid: String,
+    /// None for tasks which are not bound to an index, such as task cancelations
+    pub index_uid: Option<String>,
     pub status: TaskStatus,
 
     #[serde(rename = "type")]
@@ -36,47 +39,126 @@ pub struct TaskRef {
     pub enqueued_at: DateTime,
 }
 
+datetime::timestamp_accessors!(TaskRef {
+    enqueued_at => enqueued_at_chrono, enqueued_at_time;
+});
+
 impl TaskRef {
+    /// Cancels the task if it is enqueued or processing
+    pub async fn cancel(&self, c: &crate::Client) -> crate::Result<TaskRef> {
+        c.cancel_tasks(&crate::TaskQuery::new().uids([self.uid]))
+            .await
+    }
+
     #[cfg(feature = "tokio")]
     pub async fn wait_until_stopped(&self, c: &crate::Client) -> crate::Result<Task> {
         c.wait_for_task(self).await
     }
+
+    #[cfg(feature = "tokio")]
+    pub async fn wait_with(
+        &self,
+        c: &crate::Client,
+        opts: &crate::WaitOptions,
+    ) -> crate::Result<Task> {
+        c.wait_for_task_with(self, opts).await
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Task {
     pub uid: u64,
-    pub index_uid: String,
+    /// None for tasks which are not bound to an index, such as dumps
+    pub index_uid: Option<String>,
     pub status: TaskStatus,
     #[serde(flatten)]
     pub kind: TaskKind,
     pub canceled_by: Option<u64>,
+    /// Batch the task was processed in, None until processing starts
+    #[serde(default)]
+    pub batch_uid: Option<u64>,
     pub error: Option<TaskError>,
+    /// Time spent processing the task
     pub duration: Option<String>,
     pub enqueued_at: DateTime,
     pub started_at: Option<DateTime>,
     pub finished_at: Option<DateTime>,
 }
 
+datetime::timestamp_accessors!(Task {
+    enqueued_at => enqueued_at_chrono, enqueued_at_time;
+} optional {
+    started_at => started_at_chrono, started_at_time;
+    finished_at => finished_at_chrono, finished_at_time;
+});
+
 impl Task {
+    /// Time the task spent enqueued before processing started
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    pub fn queue_latency(&self) -> Option<std::time::Duration> {
+        datetime::elapsed(Some(&self.enqueued_at), self.started_at.as_deref())
+    }
+
+    /// Time the task spent processing, None until it has stopped
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    pub fn processing_time(&self) -> Option<std::time::Duration> {
+        self.duration_std()
+            .or_else(|| datetime::elapsed(self.started_at.as_deref(), self.finished_at.as_deref()))
+    }
+
+    /// `duration` parsed, None if unset or invalid
+    pub fn duration_std(&self) -> Option<std::time::Duration> {
+        datetime::parse_iso8601_duration(self.duration.as_deref()?)
+    }
+
     #[cfg(feature = "tokio")]
     pub async fn wait_until_stopped(&self, c: &crate::Client) -> crate::Result<Task> {
         c.wait_for_task(self).await
     }
+
+    #[cfg(feature = "tokio")]
+    pub async fn wait_with(
+        &self,
+        c: &crate::Client,
+        opts: &crate::WaitOptions,
+    ) -> crate::Result<Task> {
+        c.wait_for_task_with(self, opts).await
+    }
+}
+
+/// Stopped task used by the tests waiting for tasks
+#[cfg(all(test, feature = "tokio"))]
+pub(crate) fn test_task(uid: u64, status: TaskStatus) -> Task {
+    Task {
+        uid,
+        index_uid: Some(String::from("afejkone")),
+        status,
+        kind: TaskKind::IndexDeletion {
+            deleted_documents: None,
+        },
+        canceled_by: None,
+        batch_uid: None,
+        error: None,
+        duration: None,
+        enqueued_at: String::from("fejkedtime"),
+        started_at: None,
+        finished_at: None,
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskError {
     pub message: String,
-    pub code: String,
+    pub code: ErrorCode,
     #[serde(rename = "type")]
-    pub type_field: String,
-    pub link: String,
+    pub type_field: ErrorType,
+    #[serde(default)]
+    pub link: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TaskStatus {
     Enqueued,
@@ -92,8 +174,15 @@ impl TaskStatus {
     }
 }
 
+// Serde impls derived with `remote = "Self"` are wrapped by the trait
+// impls below, which only fall back to `Unknown` for unknown task types.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", tag = "type", content = "details")]
+#[serde(
+    remote = "Self",
+    rename_all = "camelCase",
+    tag = "type",
+    content = "details"
+)]
 pub enum TaskKind {
     #[serde(rename_all = "camelCase")]
     IndexCreation {
@@ -109,7 +198,7 @@ pub enum TaskKind {
     },
     #[serde(rename_all = "camelCase")]
     IndexSwap {
-        swaps: serde_json::Value,
+        swaps: Vec<IndexSwap>,
     },
     #[serde(rename_all = "camelCase")]
     DocumentAdditionOrUpdate {
@@ -120,14 +209,24 @@ pub enum TaskKind {
     DocumentDeletion {
         provided_ids: Option<u64>,
         original_filter: Option<String>,
-        deleted_documents: u64,
+        /// null when the task status is enqueued or processing
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentEdition {
+        original_filter: Option<String>,
+        context: Option<serde_json::Value>,
+        function: Option<String>,
+        /// null when the task status is enqueued or processing
+        edited_documents: Option<u64>,
     },
 
-    SettingsUpdate(Box<IndexSettings>),
+    SettingsUpdate(Box<SettingsPatch>),
 
     #[serde(rename_all = "camelCase")]
     DumpCreation {
-        dump_uid: i64,
+        /// null when the task status is enqueued or processing
+        dump_uid: Option<String>,
     },
     #[serde(rename_all = "camelCase")]
     TaskCancelation {
@@ -138,7 +237,7 @@ pub enum TaskKind {
         /// The number of tasks successfully canceled.
         /// If the task cancelation fails, this will be 0.
         /// null when the task status is enqueued or processing
-        canceled_tasks: i64,
+        canceled_tasks: Option<i64>,
 
         /// The filter used in the cancel task request
         original_filter: Option<String>,
@@ -151,7 +250,7 @@ pub enum TaskKind {
         matched_tasks: u64,
         /// The number of tasks successfully deleted. If the task deletion fails,
         /// this will be 0. null when the task status is enqueued or processing
-        deleted_tasks: u64,
+        deleted_tasks: Option<u64>,
 
         /// The filter used in the delete task request
         original_filter: Option<String>,
@@ -159,10 +258,109 @@ pub enum TaskKind {
 
     #[serde(rename_all = "camelCase")]
     SnapshotCreation,
+
+    #[serde(rename_all = "camelCase")]
+    UpgradeDatabase {
+        upgrade_from: String,
+        upgrade_to: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndexCompaction {
+        /// Human readable size, such as `12.5 MiB`
+        pre_compaction_size: Option<String>,
+        /// null when the task status is enqueued or processing
+        post_compaction_size: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Export {
+        url: String,
+        /// Redacted by meilisearch
+        api_key: Option<String>,
+        /// Human readable size, such as `50 MiB`
+        payload_size: Option<String>,
+        /// Exported indexes keyed by index uid pattern
+        #[serde(default)]
+        indexes: HashMap<String, ExportIndex>,
+    },
+    /// Details of topology changes are not stabilized by meilisearch
+    /// and are kept as is.
+    NetworkTopologyChange(Option<serde_json::Value>),
+
+    /// Task type not known to this version of the crate
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        details: serde_json::Value,
+    },
+}
+
+impl serde::Serialize for TaskKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Unknown { kind, details } => Tagged { kind, details }.serialize(serializer),
+            known => TaskKind::serialize(known, serializer),
+        }
+    }
+}
+
+/// Task type and details, before the details are parsed
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Tagged<K, D> {
+    #[serde(rename = "type")]
+    kind: K,
+    #[serde(default)]
+    details: D,
+}
+
+impl<'de> serde::Deserialize<'de> for TaskKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let Tagged { kind, details } =
+            Tagged::<TaskKindRef, serde_json::Value>::deserialize(deserializer)?;
+
+        match kind {
+            TaskKindRef::Unknown(kind) => Ok(Self::Unknown { kind, details }),
+
+            // errors in the details of known types are not hidden by Unknown
+            kind => TaskKind::deserialize(serde_json::json!({ "type": kind, "details": details }))
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSwap {
+    pub indexes: [String; 2],
+    /// Whether the first index was renamed to the second instead of swapped
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rename: bool,
+}
+
+impl IndexSwap {
+    pub fn new(a: impl Into<String>, b: impl Into<String>) -> Self {
+        Self {
+            indexes: [a.into(), b.into()],
+            rename: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct ExportIndex {
+    pub filter: Option<serde_json::Value>,
+    #[serde(default)]
+    pub override_settings: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum TaskKindRef {
     IndexCreation,
     IndexUpdate,
@@ -170,12 +368,21 @@ pub enum TaskKindRef {
     IndexSwap,
     DocumentAdditionOrUpdate,
     DocumentDeletion,
+    DocumentEdition,
     SettingsUpdate,
     DumpCreation,
     TaskCancelation,
     TaskDeletion,
 
     SnapshotCreation,
+    UpgradeDatabase,
+    IndexCompaction,
+    Export,
+    NetworkTopologyChange,
+
+    /// Task type not known to this version of the crate
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[cfg(test)]
@@ -213,4 +420,190 @@ mod tests {
             TaskKind::DocumentAdditionOrUpdate { .. }
         ))
     }
+
+    #[test]
+    fn deserialize_task_cancelation_ref() {
+        let s = r#"
+{"taskUid":9,"indexUid":null,"status":"enqueued","type":"taskCancelation","enqueuedAt":"2024-01-01T00:00:00Z"}"#;
+
+        let task_ref = serde_json::from_str::<TaskRef>(s).expect("deser");
+
+        assert_eq!(task_ref.kind, TaskKindRef::TaskCancelation);
+        assert_eq!(task_ref.index_uid, None);
+    }
+
+    #[test]
+    fn deserialize_task_without_index() {
+        let s = r#"
+{"uid":5,"indexUid":null,"status":"succeeded","type":"snapshotCreation","details":null,
+ "canceledBy":null,"error":null,"duration":"PT0.1S","enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert_eq!(task.index_uid, None);
+    }
+
+    #[test]
+    fn deserialize_enqueued_document_deletion_by_filter() {
+        let s = r#"
+{"uid":3,"indexUid":"users","status":"enqueued","type":"documentDeletion",
+ "details":{"providedIds":0,"originalFilter":"\"tenant_id = 42\"","deletedDocuments":null},
+ "canceledBy":null,"error":null,"duration":null,"enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert!(matches!(
+            task.kind,
+            TaskKind::DocumentDeletion {
+                deleted_documents: None,
+                original_filter: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn deserialize_document_edition() {
+        let s = r#"
+{"uid":4,"indexUid":"users","status":"succeeded","type":"documentEdition",
+ "details":{"context":{"n":1},"function":"doc.n += context.n","originalFilter":null,"editedDocuments":12},
+ "canceledBy":null,"error":null,"duration":"PT0.1S","enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert!(matches!(
+            task.kind,
+            TaskKind::DocumentEdition {
+                edited_documents: Some(12),
+                ..
+            }
+        ));
+    }
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    #[test]
+    fn task_timings() {
+        let s = r#"
+{"uid":6,"indexUid":"users","status":"succeeded","type":"indexDeletion","details":{"deletedDocuments":0},
+ "canceledBy":null,"error":null,"duration":"PT0.012S","enqueuedAt":"2024-01-01T00:00:00Z",
+ "startedAt":"2024-01-01T00:00:01.5Z","finishedAt":"2024-01-01T00:00:01.512Z"}"#;
+
+        let mut task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert_eq!(
+            task.queue_latency(),
+            Some(std::time::Duration::from_millis(1_500))
+        );
+        assert_eq!(
+            task.processing_time(),
+            Some(std::time::Duration::from_millis(12))
+        );
+
+        task.duration = None;
+        assert_eq!(
+            task.processing_time(),
+            Some(std::time::Duration::from_millis(12))
+        );
+
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            task.started_at_chrono()
+                .expect("started at")
+                .map(|at| at.timestamp_millis()),
+            Some(1_704_067_201_500)
+        );
+        #[cfg(feature = "time")]
+        assert_eq!(
+            task.finished_at_time()
+                .expect("finished at")
+                .map(|at| at.unix_timestamp()),
+            Some(1_704_067_201)
+        );
+
+        let value = serde_json::to_value(&task).expect("ser");
+        assert_eq!(
+            serde_json::from_value::<Task>(value).expect("round trip"),
+            task
+        );
+
+        task.enqueued_at = String::from("yesterday");
+        assert_eq!(task.queue_latency(), None);
+        #[cfg(feature = "chrono")]
+        assert!(matches!(
+            task.enqueued_at_chrono(),
+            Err(crate::Error::InvalidTimestamp { value, .. }) if value == "yesterday"
+        ));
+        #[cfg(feature = "time")]
+        assert!(task.enqueued_at_time().is_err());
+    }
+
+    #[test]
+    fn deserialize_index_swap() {
+        let s = r#"
+{"uid":7,"indexUid":null,"status":"succeeded","type":"indexSwap",
+ "details":{"swaps":[{"indexes":["users","users_tmp"],"rename":false}]},
+ "canceledBy":null,"error":null,"duration":"PT0.1S","enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert_eq!(
+            task.kind,
+            TaskKind::IndexSwap {
+                swaps: vec![IndexSwap {
+                    indexes: [String::from("users"), String::from("users_tmp")],
+                    rename: false,
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_task_kind_is_kept() {
+        let s = r#"
+{"uid":8,"indexUid":null,"status":"enqueued","type":"brandNewTask","details":{"n":1},
+ "canceledBy":null,"error":null,"duration":null,"enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let task = serde_json::from_str::<Task>(s).expect("deser");
+
+        assert_eq!(
+            task.kind,
+            TaskKind::Unknown {
+                kind: String::from("brandNewTask"),
+                details: serde_json::json!({"n": 1}),
+            }
+        );
+
+        let value = serde_json::to_value(&task).expect("ser");
+        assert_eq!(value["type"], "brandNewTask");
+        assert_eq!(value["details"]["n"], 1);
+
+        let task_ref = serde_json::from_str::<TaskRef>(
+            r#"{"taskUid":8,"indexUid":null,"status":"enqueued","type":"brandNewTask","enqueuedAt":"2024-01-01T00:00:00Z"}"#,
+        )
+        .expect("deser ref");
+
+        assert_eq!(
+            task_ref.kind,
+            TaskKindRef::Unknown(String::from("brandNewTask"))
+        );
+    }
+
+    #[test]
+    fn invalid_details_of_known_kind_fail() {
+        let s = r#"
+{"uid":9,"indexUid":"users","status":"succeeded","type":"documentAdditionOrUpdate",
+ "details":{"receivedDocuments":"many"},"canceledBy":null,"error":null,"duration":null,
+ "enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}"#;
+
+        let err = serde_json::from_str::<Task>(s).expect_err("invalid details");
+        assert!(err.to_string().contains("invalid type"), "{err}");
+    }
+
+    #[test]
+    fn serialize_index_swap_request() {
+        assert_eq!(
+            serde_json::to_string(&[IndexSwap::new("users", "users_tmp")]).expect("ser"),
+            r#"[{"indexes":["users","users_tmp"]}]"#
+        );
+    }
 }
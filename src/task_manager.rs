@@ -122,6 +122,7 @@ impl TaskManager {
 mod tests {
 
     use super::*;
+    use crate::{task::test_task, TaskStatus};
 
     #[tokio::test]
     async fn single_waiter_on_pending_task() {
@@ -135,7 +136,9 @@ mod tests {
             task_uid
         });
 
-        manager.handle_task(successful_task(task_uid)).await;
+        manager
+            .handle_task(test_task(task_uid, TaskStatus::Succeeded))
+            .await;
 
         assert!(waiter_handle.await.is_ok());
     }
@@ -164,7 +167,9 @@ mod tests {
             task_uid
         });
 
-        manager.handle_task(successful_task(task_uid)).await;
+        manager
+            .handle_task(test_task(task_uid, TaskStatus::Succeeded))
+            .await;
 
         assert!(handle1.await.is_ok());
         assert!(handle2.await.is_ok());
@@ -177,7 +182,9 @@ mod tests {
 
         let task_uid = 232;
 
-        manager.handle_task(successful_task(task_uid)).await;
+        manager
+            .handle_task(test_task(task_uid, TaskStatus::Succeeded))
+            .await;
         manager.subscribe_for_task(task_uid).await;
     }
 
@@ -193,7 +200,9 @@ mod tests {
             task_uid
         });
 
-        manager.handle_task(successful_task(task_uid)).await;
+        manager
+            .handle_task(test_task(task_uid, TaskStatus::Succeeded))
+            .await;
 
         assert!(handle1.await.is_ok());
 
@@ -218,28 +227,13 @@ mod tests {
         }
 
         for i in 0..3 {
-            manager.handle_task(successful_task(i as u64)).await;
+            manager
+                .handle_task(test_task(i as u64, TaskStatus::Succeeded))
+                .await;
         }
 
         for handle in handles {
             assert!(handle.await.is_ok());
         }
     }
-
-    fn successful_task(uid: u64) -> Task {
-        Task {
-            uid,
-            index_uid: String::from("afejkone"),
-            status: crate::TaskStatus::Succeeded,
-            kind: crate::TaskKind::IndexDeletion {
-                deleted_documents: None,
-            },
-            canceled_by: None,
-            error: None,
-            duration: None,
-            enqueued_at: String::from("fejkedtime"),
-            started_at: None,
-            finished_at: None,
-        }
-    }
 }
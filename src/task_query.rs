@@ -0,0 +1,337 @@
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use serde::ser::Error as _;
+
+use crate::{Client, DateTime, Task, TaskKindRef, TaskStatus};
+
+/// Filters for listing, canceling and deleting tasks, and for listing batches.
+///
+/// Filters of different kinds are combined with AND, the values
+/// of a single filter with OR.
+#[derive(Debug, Default, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskQuery {
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    uids: Vec<u64>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    statuses: Vec<TaskStatus>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    types: Vec<TaskKindRef>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    index_uids: Vec<String>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    canceled_by: Vec<u64>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "comma_separated"
+    )]
+    batch_uids: Vec<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before_enqueued_at: Option<DateTime>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after_enqueued_at: Option<DateTime>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before_started_at: Option<DateTime>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after_started_at: Option<DateTime>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before_finished_at: Option<DateTime>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after_finished_at: Option<DateTime>,
+
+    /// Maximum number of tasks returned
+    /// default 20
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+
+    /// Uid of the first task returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<u64>,
+}
+
+fn comma_separated<S, T>(items: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    T: serde::Serialize,
+{
+    let mut out = String::new();
+
+    for (i, item) in items.iter().enumerate() {
+        if 0 < i {
+            out.push(',');
+        }
+
+        match serde_json::to_value(item).map_err(S::Error::custom)? {
+            serde_json::Value::String(s) => out.push_str(&s),
+            v => out.push_str(&v.to_string()),
+        }
+    }
+
+    serializer.serialize_str(&out)
+}
+
+/// Date filters taking `chrono` and `time` timestamps
+macro_rules! typed_date_filters {
+    ($($field:ident => $chrono:ident, $time:ident;)+) => {
+        $(
+            #[doc = concat!("[`TaskQuery::", stringify!($field), "`] with a `chrono` timestamp")]
+            #[cfg(feature = "chrono")]
+            pub fn $chrono(self, at: chrono::DateTime<chrono::Utc>) -> Self {
+                self.$field(crate::datetime::from_chrono(at))
+            }
+
+            #[doc = concat!("[`TaskQuery::", stringify!($field), "`] with a `time` timestamp")]
+            #[cfg(feature = "time")]
+            pub fn $time(self, at: time::OffsetDateTime) -> Self {
+                self.$field(crate::datetime::from_time(at))
+            }
+        )+
+    };
+}
+
+impl TaskQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn uids(mut self, uids: impl IntoIterator<Item = u64>) -> Self {
+        self.uids = uids.into_iter().collect();
+        self
+    }
+
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = TaskStatus>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn types(mut self, types: impl IntoIterator<Item = TaskKindRef>) -> Self {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    pub fn index_uids<S>(mut self, index_uids: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.index_uids = index_uids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Tasks canceled by any of the given task cancelation tasks
+    pub fn canceled_by(mut self, uids: impl IntoIterator<Item = u64>) -> Self {
+        self.canceled_by = uids.into_iter().collect();
+        self
+    }
+
+    /// Tasks processed in any of the given batches
+    pub fn batch_uids(mut self, uids: impl IntoIterator<Item = u64>) -> Self {
+        self.batch_uids = uids.into_iter().collect();
+        self
+    }
+
+    pub fn before_enqueued_at(mut self, at: DateTime) -> Self {
+        self.before_enqueued_at = Some(at);
+        self
+    }
+
+    pub fn after_enqueued_at(mut self, at: DateTime) -> Self {
+        self.after_enqueued_at = Some(at);
+        self
+    }
+
+    pub fn before_started_at(mut self, at: DateTime) -> Self {
+        self.before_started_at = Some(at);
+        self
+    }
+
+    pub fn after_started_at(mut self, at: DateTime) -> Self {
+        self.after_started_at = Some(at);
+        self
+    }
+
+    pub fn before_finished_at(mut self, at: DateTime) -> Self {
+        self.before_finished_at = Some(at);
+        self
+    }
+
+    pub fn after_finished_at(mut self, at: DateTime) -> Self {
+        self.after_finished_at = Some(at);
+        self
+    }
+
+    typed_date_filters! {
+        before_enqueued_at => before_enqueued_at_chrono, before_enqueued_at_time;
+        after_enqueued_at => after_enqueued_at_chrono, after_enqueued_at_time;
+        before_started_at => before_started_at_chrono, before_started_at_time;
+        after_started_at => after_started_at_chrono, after_started_at_time;
+        before_finished_at => before_finished_at_chrono, before_finished_at_time;
+        after_finished_at => after_finished_at_chrono, after_finished_at_time;
+    }
+
+    pub fn limit(mut self, n: Option<u32>) -> Self {
+        self.limit = n;
+        self
+    }
+
+    pub fn from(mut self, uid: Option<u64>) -> Self {
+        self.from = uid;
+        self
+    }
+
+    /// Removes limit and from, which are rejected when
+    /// canceling or deleting tasks.
+    pub(crate) fn without_paging(&self) -> Self {
+        Self {
+            limit: None,
+            from: None,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksPage {
+    /// Tasks in descending order of uid
+    pub results: Vec<Task>,
+
+    /// Total number of tasks matching the query
+    pub total: u64,
+
+    pub limit: u32,
+
+    pub from: Option<u64>,
+
+    /// Value of `from` for the next page, None on the last page
+    pub next: Option<u64>,
+}
+
+pub(crate) fn stream(client: Client, query: TaskQuery) -> impl Stream<Item = crate::Result<Task>> {
+    let pages = TaskPages {
+        client,
+        query,
+        done: false,
+    };
+
+    stream::try_unfold(pages, TaskPages::next)
+        .map_ok(|tasks| stream::iter(tasks).map(Ok))
+        .try_flatten()
+}
+
+struct TaskPages {
+    client: Client,
+    query: TaskQuery,
+    done: bool,
+}
+
+impl TaskPages {
+    async fn next(mut self) -> crate::Result<Option<(Vec<Task>, Self)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page = self.client.list_tasks(&self.query).await?;
+
+        self.done = page.next.is_none();
+        self.query.from = page.next;
+
+        Ok(Some((page.results, self)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn serialize_query_string() {
+        let query = TaskQuery::new()
+            .statuses([TaskStatus::Failed, TaskStatus::Canceled])
+            .types([TaskKindRef::DocumentAdditionOrUpdate])
+            .index_uids(["users"])
+            .uids([1, 2])
+            .after_finished_at(String::from("2024-01-01T00:00:00+01:00"))
+            .limit(Some(50));
+
+        let req = reqwest::Client::new()
+            .get("http://localhost/tasks")
+            .query(&query)
+            .build()
+            .expect("build");
+
+        assert_eq!(
+            req.url().query(),
+            Some(
+                "uids=1%2C2&statuses=failed%2Ccanceled&types=documentAdditionOrUpdate\
+                 &indexUids=users&afterFinishedAt=2024-01-01T00%3A00%3A00%2B01%3A00&limit=50"
+            )
+        );
+    }
+
+    #[test]
+    fn cancelation_query_has_no_paging() {
+        let query = TaskQuery::new()
+            .statuses([TaskStatus::Enqueued])
+            .limit(Some(10))
+            .from(Some(3))
+            .without_paging();
+
+        let req = reqwest::Client::new()
+            .post("http://localhost/tasks/cancel")
+            .query(&query)
+            .build()
+            .expect("build");
+
+        assert_eq!(req.url().query(), Some("statuses=enqueued"));
+    }
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    #[test]
+    fn typed_date_filters() {
+        #[cfg(feature = "chrono")]
+        {
+            let at = chrono::DateTime::from_timestamp_millis(1_704_067_201_500).expect("at");
+            let query = TaskQuery::new().after_enqueued_at_chrono(at);
+            assert_eq!(
+                query.after_enqueued_at.as_deref(),
+                Some("2024-01-01T00:00:01.500Z")
+            );
+        }
+
+        #[cfg(feature = "time")]
+        {
+            let at = time::OffsetDateTime::from_unix_timestamp(1_704_067_201).expect("at");
+            let query = TaskQuery::new().before_finished_at_time(at);
+            assert_eq!(
+                query.before_finished_at.as_deref(),
+                Some("2024-01-01T00:00:01Z")
+            );
+        }
+    }
+}
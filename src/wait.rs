@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use crate::{Error, TaskStatus};
+use crate::{Task, TaskError};
+
+/// Controls how long, and how often, to poll when waiting for tasks.
+///
+/// Polling backs off from `initial_interval` by `backoff_factor` after
+/// every poll, up to `max_interval`. With the `hooks` feature polling only
+/// starts as a fallback when no webhook has arrived, see
+/// [`WaitOptions::poll_fallback_after`].
+#[derive(Debug, Clone)]
+pub struct WaitOptions {
+    timeout: Option<Duration>,
+    initial_interval: Duration,
+    max_interval: Duration,
+    backoff_factor: f64,
+    poll_fallback_after: Option<Duration>,
+    fail_fast: bool,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(300)),
+            initial_interval: Duration::from_millis(100),
+            max_interval: Duration::from_secs(2),
+            backoff_factor: 1.5,
+            poll_fallback_after: Some(Duration::from_secs(5)),
+            fail_fast: false,
+        }
+    }
+}
+
+impl WaitOptions {
+    /// Give up waiting after `timeout`, None waits forever
+    /// default 5 minutes
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Delay before the first poll
+    /// default 100ms
+    pub fn initial_interval(mut self, dur: Duration) -> Self {
+        self.initial_interval = dur;
+        self
+    }
+
+    /// Upper bound of the delay between polls
+    /// default 2s
+    pub fn max_interval(mut self, dur: Duration) -> Self {
+        self.max_interval = dur;
+        self
+    }
+
+    /// Factor the delay grows by after every poll, at least 1.0
+    /// default 1.5
+    pub fn backoff_factor(mut self, factor: f64) -> Self {
+        self.backoff_factor = factor.max(1.0);
+        self
+    }
+
+    /// With the `hooks` feature, start polling if the task has not been
+    /// reported through a webhook after this delay. None relies on
+    /// webhooks only.
+    /// default 5s
+    pub fn poll_fallback_after(mut self, delay: Option<Duration>) -> Self {
+        self.poll_fallback_after = delay;
+        self
+    }
+
+    /// When waiting for many tasks, stop at the first failed one and
+    /// return [`Error::TaskFailed`](crate::Error::TaskFailed)
+    /// default false
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    #[cfg_attr(not(feature = "hooks"), allow(dead_code))]
+    pub(crate) fn get_poll_fallback_after(&self) -> Option<Duration> {
+        self.poll_fallback_after
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn get_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Delays between polls
+    pub(crate) fn intervals(&self) -> impl Iterator<Item = Duration> + '_ {
+        std::iter::successors(Some(self.initial_interval), |interval| {
+            let secs = interval.as_secs_f64() * self.backoff_factor;
+            Some(
+                Duration::try_from_secs_f64(secs)
+                    .map_or(self.max_interval, |next| next.min(self.max_interval)),
+            )
+        })
+        .map(|interval| interval.min(self.max_interval))
+    }
+}
+
+/// Outcome of waiting for many tasks
+#[derive(Debug, Default)]
+pub struct TasksReport {
+    pub succeeded: Vec<Task>,
+    pub failed: Vec<Task>,
+    pub canceled: Vec<Task>,
+}
+
+impl TasksReport {
+    pub fn all_succeeded(&self) -> bool {
+        self.failed.is_empty() && self.canceled.is_empty()
+    }
+
+    /// Errors of the failed tasks by task uid
+    pub fn errors(&self) -> impl Iterator<Item = (u64, &TaskError)> {
+        self.failed
+            .iter()
+            .filter_map(|task| task.error.as_ref().map(|err| (task.uid, err)))
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn record(&mut self, task: Task, fail_fast: bool) -> crate::Result<()> {
+        match task.status {
+            TaskStatus::Failed if fail_fast => return Err(Error::TaskFailed(Box::new(task))),
+            TaskStatus::Failed => self.failed.push(task),
+            TaskStatus::Canceled => self.canceled.push(task),
+            _ => self.succeeded.push(task),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+
+    use super::*;
+    use crate::task::test_task;
+
+    #[test]
+    fn intervals_back_off_until_max() {
+        let opts = WaitOptions::default()
+            .initial_interval(Duration::from_millis(100))
+            .max_interval(Duration::from_millis(300))
+            .backoff_factor(2.0);
+
+        assert_eq!(
+            opts.intervals().take(4).collect::<Vec<_>>(),
+            [100, 200, 300, 300].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn invalid_backoff_factor_does_not_panic() {
+        for factor in [-2.0, f64::NAN, f64::INFINITY] {
+            let opts = WaitOptions::default()
+                .initial_interval(Duration::from_millis(100))
+                .max_interval(Duration::from_millis(300))
+                .backoff_factor(factor);
+
+            let intervals = opts.intervals().take(3).collect::<Vec<_>>();
+            assert!(intervals
+                .iter()
+                .all(|i| Duration::from_millis(100) <= *i && *i <= Duration::from_millis(300)));
+        }
+    }
+
+    #[test]
+    fn report_groups_tasks_by_status() {
+        let mut report = TasksReport::default();
+
+        for (uid, status) in [
+            (1, TaskStatus::Succeeded),
+            (2, TaskStatus::Failed),
+            (3, TaskStatus::Canceled),
+        ] {
+            report
+                .record(test_task(uid, status), false)
+                .expect("record");
+        }
+
+        assert_eq!(report.succeeded[0].uid, 1);
+        assert_eq!(report.failed[0].uid, 2);
+        assert_eq!(report.canceled[0].uid, 3);
+        assert!(!report.all_succeeded());
+    }
+
+    #[test]
+    fn report_fails_fast() {
+        let mut report = TasksReport::default();
+
+        assert!(report
+            .record(test_task(1, TaskStatus::Canceled), true)
+            .is_ok());
+        assert!(matches!(
+            report.record(test_task(2, TaskStatus::Failed), true),
+            Err(Error::TaskFailed(task)) if task.uid == 2
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+use reqwest::header::{self, HeaderMap};
+use std::{io::Read, sync::Arc};
+use tracing::warn;
+
+use crate::{Task, TaskManager};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("missing or invalid authorization header")]
+    Unauthorized,
+
+    #[error("decompressing body: {0}")]
+    Decompress(std::io::Error),
+
+    #[error("decompressed body larger than {limit} bytes")]
+    BodyTooLarge { limit: usize },
+}
+
+/// Receives meilisearch task webhooks and feeds the tasks to
+/// the client's [`TaskManager`].
+///
+/// Bodies are NDJSON, one task per line, optionally gzip compressed.
+/// Lines which are not valid tasks are skipped with a warning.
+#[derive(Clone)]
+pub struct WebhookHandler {
+    task_manager: TaskManager,
+    authorization: Option<Arc<str>>,
+    max_body_size: usize,
+}
+
+impl WebhookHandler {
+    pub(crate) fn new(task_manager: TaskManager) -> Self {
+        Self {
+            task_manager,
+            authorization: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Only accept requests with exactly this authorization header,
+    /// as configured with `--task-webhook-authorization-header`.
+    pub fn authorization(mut self, expected: impl Into<String>) -> Self {
+        self.authorization = Some(Arc::from(expected.into()));
+        self
+    }
+
+    /// Maximum size of a body once decompressed
+    /// default 64MiB
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Handles a webhook request, returns the number of tasks received
+    pub async fn handle(&self, headers: &HeaderMap, body: &[u8]) -> Result<usize, WebhookError> {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .map(|value| value.as_bytes());
+
+        if let Some(expected) = &self.authorization {
+            if !authorization.is_some_and(|given| constant_time_eq(given, expected.as_bytes())) {
+                warn!("meili: rejected webhook with missing or invalid authorization");
+                return Err(WebhookError::Unauthorized);
+            }
+        }
+
+        let gzipped = headers
+            .get(header::CONTENT_ENCODING)
+            .is_some_and(|enc| enc.as_bytes().eq_ignore_ascii_case(b"gzip"))
+            || body.starts_with(&GZIP_MAGIC);
+
+        let tasks = if gzipped {
+            let mut buf = Vec::new();
+            flate2::read::GzDecoder::new(body)
+                .take(self.max_body_size as u64 + 1)
+                .read_to_end(&mut buf)
+                .map_err(WebhookError::Decompress)?;
+
+            if self.max_body_size < buf.len() {
+                return Err(WebhookError::BodyTooLarge {
+                    limit: self.max_body_size,
+                });
+            }
+
+            parse_ndjson(&buf)
+        } else {
+            parse_ndjson(body)
+        };
+
+        let n = tasks.len();
+        for task in tasks {
+            self.task_manager.handle_task(task).await;
+        }
+
+        Ok(n)
+    }
+
+    /// Axum handler which can be mounted on any router, e.g
+    /// `Router::new().route("/meili/tasks", handler.into_method_router())`.
+    #[cfg(feature = "axum")]
+    pub fn into_method_router<S>(self) -> axum::routing::MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        use axum::http::StatusCode;
+
+        axum::routing::post(move |headers: HeaderMap, body: bytes::Bytes| async move {
+            match self.handle(&headers, &body).await {
+                Ok(_) => StatusCode::NO_CONTENT,
+                Err(WebhookError::Unauthorized) => StatusCode::UNAUTHORIZED,
+                Err(WebhookError::BodyTooLarge { .. }) => StatusCode::PAYLOAD_TOO_LARGE,
+                Err(err) => {
+                    warn!("meili: invalid webhook: {err}");
+                    StatusCode::BAD_REQUEST
+                }
+            }
+        })
+    }
+}
+
+fn parse_ndjson(body: &[u8]) -> Vec<Task> {
+    body.split(|b| *b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.trim_ascii().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_slice(line) {
+            Ok(task) => Some(task),
+            Err(err) => {
+                warn!(
+                    "meili: skipping invalid task on webhook line {}: {err}",
+                    i + 1
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Write;
+
+    const TASKS: &str = r#"{"uid":1,"indexUid":"users","status":"succeeded","type":"indexDeletion","details":{"deletedDocuments":0},"canceledBy":null,"error":null,"duration":"PT0.1S","enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}
+{"uid":2,"indexUid":"users","status":"failed","type":"indexDeletion","details":{"deletedDocuments":null},"canceledBy":null,"error":null,"duration":"PT0.1S","enqueuedAt":"2024-01-01T00:00:00Z","startedAt":null,"finishedAt":null}
+"#;
+
+    fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), header::HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn feeds_all_tasks_to_manager() {
+        let manager = TaskManager::default();
+        let handler = WebhookHandler::new(manager.clone());
+
+        let n = handler
+            .handle(&HeaderMap::new(), TASKS.as_bytes())
+            .await
+            .expect("handle");
+        assert_eq!(n, 2);
+
+        let task = manager
+            .subscribe_for_task(2)
+            .await
+            .wait()
+            .await
+            .expect("task");
+        assert_eq!(task.status, crate::TaskStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn decompresses_gzip_body() {
+        let manager = TaskManager::default();
+        let handler = WebhookHandler::new(manager.clone());
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(TASKS.as_bytes()).expect("write");
+        let body = encoder.finish().expect("finish");
+
+        let n = handler
+            .handle(&headers(&[(header::CONTENT_ENCODING, "gzip")]), &body)
+            .await
+            .expect("handle");
+        assert_eq!(n, 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_authorization() {
+        let handler = WebhookHandler::new(TaskManager::default()).authorization("Bearer secret");
+
+        assert!(matches!(
+            handler.handle(&HeaderMap::new(), TASKS.as_bytes()).await,
+            Err(WebhookError::Unauthorized)
+        ));
+
+        assert!(matches!(
+            handler
+                .handle(
+                    &headers(&[(header::AUTHORIZATION, "Bearer wrong")]),
+                    TASKS.as_bytes()
+                )
+                .await,
+            Err(WebhookError::Unauthorized)
+        ));
+
+        assert!(handler
+            .handle(
+                &headers(&[(header::AUTHORIZATION, "Bearer secret")]),
+                TASKS.as_bytes()
+            )
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn skips_invalid_tasks() {
+        let handler = WebhookHandler::new(TaskManager::default());
+
+        let body = format!("{{\"uid\":3}}\n{TASKS}");
+
+        assert_eq!(
+            handler
+                .handle(&HeaderMap::new(), body.as_bytes())
+                .await
+                .expect("handle"),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_large_decompressed_body() {
+        let handler = WebhookHandler::new(TaskManager::default()).max_body_size(100);
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(TASKS.as_bytes()).expect("write");
+        let body = encoder.finish().expect("finish");
+
+        assert!(matches!(
+            handler.handle(&HeaderMap::new(), &body).await,
+            Err(WebhookError::BodyTooLarge { limit: 100 })
+        ));
+    }
+}